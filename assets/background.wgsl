@@ -3,8 +3,16 @@
 
 #import bevy_sprite::mesh2d_vertex_output::VertexOutput
 
+// 字段顺序必须与 background.rs 中的 BackgroundMaterial 保持一致
 struct BackgroundMaterial {
     time: f32,
+    star_density: f32,
+    intensity: f32,
+    flash: f32,
+    scroll_offset: vec2<f32>,
+    nebula_near: vec4<f32>,
+    nebula_far: vec4<f32>,
+    flash_color: vec4<f32>,
 };
 
 @group(2) @binding(0)
@@ -29,10 +37,10 @@ const saturation = 0.850;
 fn fragment(
     in: VertexOutput
 ) -> @location(0) vec4<f32> {
-    let dir = vec3<f32>(in.uv * zoom, 1.0);
-    let time = background.time * speed + 0.25;
+    let dir = vec3<f32>(in.uv * zoom * background.star_density, 1.0);
+    let scroll = background.scroll_offset * speed + vec2<f32>(0.5, 0.25);
     var from_ = vec3<f32>(1.0, 0.5, 0.5);
-    from_ = from_ + vec3<f32>(time * 2., time, -2.);
+    from_ = from_ + vec3<f32>(scroll, -2.);
     
     // volumetric rendering
     var s = 0.1;
@@ -56,10 +64,12 @@ fn fragment(
             fade = fade * (1. - dm); // dark matter, don't render near
         }
         v = v + fade;
-        v = v + vec3<f32>(s, s * s, s * s * s * s) * a * brightness * fade; // coloring based on distance
+        let nebula = mix(background.nebula_near.rgb, background.nebula_far.rgb, clamp(s / 2.1, 0.0, 1.0));
+        v = v + vec3<f32>(s, s * s, s * s * s * s) * nebula * a * brightness * fade; // coloring based on distance
         fade = fade * distfading; // distance fading;
         s = s + stepsize;
     }
     v = mix(vec3<f32>(length(v)), v, saturation); // color_adjust
-    return vec4<f32>(v * 0.0006, 1.0);
+    let color = v * 0.0006 * background.intensity + background.flash_color.rgb * background.flash;
+    return vec4<f32>(color, 1.0);
 }
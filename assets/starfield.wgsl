@@ -0,0 +1,42 @@
+// 视差星空层：把画面划分为网格，每个单元按概率放置一颗星星
+
+#import bevy_sprite::mesh2d_vertex_output::VertexOutput
+
+// 字段顺序必须与 background.rs 中的 StarLayerMaterial 保持一致
+struct StarLayerMaterial {
+    offset: vec2<f32>,
+    size: vec2<f32>,
+    density: f32,
+    star_size: f32,
+    brightness: f32,
+    cell_size: f32,
+    seed: f32,
+};
+
+@group(2) @binding(0)
+var<uniform> layer: StarLayerMaterial;
+
+fn hash(p: vec2<f32>) -> f32 {
+    return fract(sin(dot(p, vec2<f32>(127.1, 311.7))) * 43758.5453);
+}
+
+@fragment
+fn fragment(
+    in: VertexOutput
+) -> @location(0) vec4<f32> {
+    // uv 的 y 轴朝下，翻转后与世界坐标方向一致
+    let pos = vec2<f32>(in.uv.x, 1.0 - in.uv.y) * layer.size + layer.offset;
+    let cell = floor(pos / layer.cell_size);
+    let local = fract(pos / layer.cell_size);
+
+    let cell_seed = cell + vec2<f32>(layer.seed);
+    if hash(cell_seed) > layer.density {
+        return vec4<f32>(0.0);
+    }
+
+    // 星星在单元内的随机位置，留出边距避免被单元边界截断
+    let star = vec2<f32>(hash(cell_seed + 1.3), hash(cell_seed + 7.7)) * 0.8 + 0.1;
+    let dist = length((local - star) * layer.cell_size);
+    let alpha = clamp(1.0 - dist / layer.star_size, 0.0, 1.0) * layer.brightness;
+    return vec4<f32>(vec3<f32>(1.0), alpha);
+}
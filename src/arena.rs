@@ -1,9 +1,47 @@
+use bevy_transform_interpolation::TranslationEasingState;
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+
 use crate::prelude::*;
 
-// 定义游戏区域的宽度和高度（屏幕大小）
+// 定义默认游戏区域的宽度和高度，同时也是初始窗口大小
 pub const ARENA_WIDTH: f32 = 1280.0;
 pub const ARENA_HEIGHT: f32 = 800.0;
 
+// 游戏模式，决定竞技场的逻辑大小等规则
+// 竞技场大小与窗口大小无关，摄像机会把竞技场等比缩放到窗口中
+#[derive(Resource, Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub enum GameMode {
+    #[default]
+    Classic,    // 经典模式，竞技场与默认窗口一样大
+    Widescreen, // 宽屏模式，16:9 的更大竞技场
+    BigArena,   // 大地图模式，竞技场有好几个屏幕大，摄像机跟随飞船
+}
+
+impl GameMode {
+    // 该模式下竞技场的逻辑大小
+    pub fn arena_size(&self) -> Vec2 {
+        match self {
+            GameMode::Classic => Vec2::new(ARENA_WIDTH, ARENA_HEIGHT),
+            GameMode::Widescreen => Vec2::new(1920.0, 1080.0),
+            GameMode::BigArena => Vec2::new(ARENA_WIDTH * 3.0, ARENA_HEIGHT * 3.0),
+        }
+    }
+
+    // 摄像机一次能看到的逻辑大小，小于竞技场时摄像机需要跟随飞船
+    pub fn view_size(&self) -> Vec2 {
+        match self {
+            GameMode::BigArena => Vec2::new(ARENA_WIDTH, ARENA_HEIGHT),
+            _ => self.arena_size(),
+        }
+    }
+
+    // 竞技场是否超出一屏
+    pub fn is_scrolling(&self) -> bool {
+        self.view_size() != self.arena_size()
+    }
+}
+
 // 表示游戏场景的数据结构（资源）
 #[derive(Debug, Resource)]
 pub struct Arena {
@@ -11,28 +49,64 @@ pub struct Arena {
     pub asteroid_spawn_timer: Timer,
     // 玩家当前得分
     pub score: u32,
+    // 竞技场的逻辑大小，由游戏模式决定
+    pub size: Vec2,
+    // 本局已经按分数奖励过的生命数
+    pub extra_lives_awarded: u32,
+}
+
+// 指定下一局使用的随机种子（资源），为 None 时每局随机选择种子
+#[derive(Resource, Debug, Default, Copy, Clone)]
+pub struct GameSeed(pub Option<u64>);
+
+// 游戏逻辑使用的随机数生成器（资源），每局开始时按种子重新创建
+// 玩法中的随机（小行星生成、碎片、道具）都必须使用它，相同种子和输入才能得到相同的过程
+#[derive(Resource, Debug, Clone, Deref, DerefMut)]
+pub struct GameRng(pub ChaCha8Rng);
+
+impl GameRng {
+    pub fn from_seed(seed: u64) -> Self {
+        GameRng(ChaCha8Rng::seed_from_u64(seed))
+    }
 }
 
-// 用于物理系统的层分类，区分玩家、激光、和小行星
+// 用于物理系统的层分类，区分玩家、激光、小行星和道具
 #[derive(PhysicsLayer, Default)]
 pub enum GameLayer {
     #[default]
     Player,
     Laser,
     Asteroid,
+    Pickup,
 }
 
-// 定义一个“受伤事件”
-// Supernova 中我们不需要传递伤害数值或类型，简单表示实体受到伤害即可
-#[derive(Event)]
-pub struct Damage;
+// 伤害来源
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum DamageSource {
+    Laser,     // 激光命中
+    Impact,    // 物理碰撞
+    Explosion, // 爆炸波及
+    GravityWell, // 被引力井吞噬
+}
+
+// 定义一个“受伤事件”，携带伤害数值、来源和伤害来自的位置
+// 小行星按数值扣除生命值；飞船按数值（向上取整）扣除生命数
+#[derive(Event, Debug, Copy, Clone)]
+pub struct Damage {
+    pub amount: f32,
+    pub source: DamageSource,
+    // 命中点（激光位置、爆炸中心或撞上来的物体位置），用于决定碎片飞散方向
+    pub point: Vec2,
+}
 
 // 定义一个插件，用于设置和管理游戏主场景
 pub struct ArenaPlugin;
 
 impl Plugin for ArenaPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(OnEnter(GameState::Setup), spawn_arena) // 进入 Setup 状态时初始化游戏场景
+        app.init_resource::<GameMode>()
+            .init_resource::<GameSeed>()
+            .add_systems(OnEnter(GameState::Setup), spawn_arena) // 进入 Setup 状态时初始化游戏场景
             .add_systems(
                 OnEnter(GameState::Running),
                 // 游戏开始运行时，启用物理模拟
@@ -47,33 +121,45 @@ impl Plugin for ArenaPlugin {
                     physics_time.pause();
                 },
             )
-            // 在游戏运行状态时启用实体位置更新逻辑
-            .add_systems(Update, movement.run_if(in_state(GameState::Running)));
+            // 在游戏运行状态时启用实体位置更新逻辑，与物理模拟一样按固定时间步运行
+            .add_systems(FixedUpdate, movement.run_if(in_state(GameState::Running)));
     }
 }
 
 // 初始化游戏场景资源
-fn spawn_arena(mut commands: Commands) {
+fn spawn_arena(mut commands: Commands, mode: Res<GameMode>, seed: Res<GameSeed>) {
+    let seed = seed.0.unwrap_or_else(|| thread_rng().gen());
+    info!("Game seed: {seed}");
+    commands.insert_resource(GameRng::from_seed(seed));
     commands.insert_resource(Arena {
         // 初始化一个5秒的小行星生成计时器
         asteroid_spawn_timer: Timer::from_seconds(5.0, TimerMode::Once),
         score: 0,
+        size: mode.arena_size(),
+        extra_lives_awarded: 0,
     });
 
-    // 设定重力为 0，避免实体受重力影响
+    // 设定重力为 0，避免实体受重力影响；场地中的引力由波次放置的引力井提供
     commands.insert_resource(Gravity::ZERO);
 }
 
+// 竞技场边缘相连，两点间的相对位置取最近的那一份
+pub fn wrapped_offset(offset: Vec2, arena_size: Vec2) -> Vec2 {
+    offset - arena_size * (offset / arena_size).round()
+}
+
 // 控制实体在屏幕边缘穿越（“屏幕环绕”效果）
-fn movement(mut query: Query<(&LinearVelocity, &mut Position)>) {
-    for (linvel, mut position) in query.iter_mut() {
+fn movement(
+    arena: Res<Arena>,
+    mut query: Query<(&LinearVelocity, &mut Position, Option<&mut TranslationEasingState>)>,
+) {
+    let half_width = arena.size.x / 2.0;
+    let half_height = arena.size.y / 2.0;
+    for (linvel, mut position, easing) in query.iter_mut() {
         let mut x = position.x;
         let mut y = position.y;
         let mut updated = false;
 
-        let half_width = ARENA_WIDTH / 2.0;
-        let half_height = ARENA_HEIGHT / 2.0;
-
         // 左右边界判断
         if x < -half_width && linvel.x < 0.0 {
             x = half_width;
@@ -96,6 +182,10 @@ fn movement(mut query: Query<(&LinearVelocity, &mut Position)>) {
         if updated {
             position.x = x;
             position.y = y;
+            // 穿越边界是瞬移，清除插值起点，避免渲染时从一侧滑到另一侧
+            if let Some(mut easing) = easing {
+                easing.start = None;
+            }
         }
     }
 }
@@ -1,22 +1,31 @@
+use bevy::platform::collections::HashMap;
+
 use crate::prelude::*;
 
 #[derive(Debug, Resource)]
 pub struct SpriteAssets {
     pub laser: Handle<Image>,
-    pub meteor_big: Handle<Image>,
-    pub meteor_med: Handle<Image>,
-    pub meteor_small: Handle<Image>,
+    pub meteors: HashMap<(AsteroidMaterial, AsteroidSize), Handle<Image>>,
+    pub pickup: Handle<Image>,
     pub player_ship: Handle<Image>,
     pub ship_explosion: Handle<Image>,
     pub ship_contact: Handle<Image>,
     pub asteroid_explosion: Handle<Image>,
 }
+impl SpriteAssets {
+    // 获取指定材质和大小的小行星精灵
+    pub fn meteor(&self, material: AsteroidMaterial, size: AsteroidSize) -> Handle<Image> {
+        self.meteors[&(material, size)].clone()
+    }
+}
+
 #[derive(Debug, Resource)]
 pub struct AudioAssets {
     pub laser_trigger: Handle<AudioSource>,
     pub ship_explosion: Handle<AudioSource>,
     pub ship_contact: Handle<AudioSource>,
     pub asteroid_explosion: Handle<AudioSource>,
+    pub extra_life: Handle<AudioSource>,
 }
 
 #[derive(Debug, Resource)]
@@ -37,9 +46,16 @@ impl Plugin for AssetsPlugin {
 fn setup(mut commands: Commands, asset_server: Res<AssetServer>) {
     commands.insert_resource(SpriteAssets {
         laser: asset_server.load("laserRed07.png"),
-        meteor_big: asset_server.load("meteorBrown_big1.png"),
-        meteor_med: asset_server.load("meteorBrown_med1.png"),
-        meteor_small: asset_server.load("meteorBrown_small1.png"),
+        // 每种材质、每种大小各一张小行星精灵
+        meteors: AsteroidMaterial::ALL
+            .iter()
+            .flat_map(|material| AsteroidSize::ALL.iter().map(move |size| (*material, *size)))
+            .map(|(material, size)| {
+                let handle = asset_server.load(material.sprite_path(size));
+                ((material, size), handle)
+            })
+            .collect(),
+        pickup: asset_server.load("sprite_sphere_256x256.png"),
         player_ship: asset_server.load("playerShip2_red.png"),
         ship_explosion: asset_server.load("explosion01.png"),
         ship_contact: asset_server.load("explosion01.png"),
@@ -50,6 +66,7 @@ fn setup(mut commands: Commands, asset_server: Res<AssetServer>) {
         ship_explosion: asset_server.load("Explosion_ship.ogg"),
         ship_contact: asset_server.load("Explosion.ogg"),
         asteroid_explosion: asset_server.load("Explosion.ogg"),
+        extra_life: asset_server.load("sfx_laser1.ogg"),
     });
     commands.insert_resource(UiAssets {
         font: asset_server.load("kenvector_future.ttf"),
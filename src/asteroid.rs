@@ -1,19 +1,22 @@
 use crate::prelude::*;
 use core::time::Duration;
+use serde::{Deserialize, Serialize};
 
 // 小行星生成事件，携带生成小行星的参数
 #[derive(Event)]
 pub struct AsteroidSpawnEvent {
-    pub size: AsteroidSize, // 小行星大小
-    pub x: f32,             // 生成位置X坐标
-    pub y: f32,             // 生成位置Y坐标
-    pub vx: f32,            // 初始水平速度
-    pub vy: f32,            // 初始垂直速度
-    pub angvel: f32,        // 初始角速度
+    pub size: AsteroidSize,         // 小行星大小
+    pub material: AsteroidMaterial, // 小行星材质
+    pub x: f32,                     // 生成位置X坐标
+    pub y: f32,                     // 生成位置Y坐标
+    pub vx: f32,                    // 初始水平速度
+    pub vy: f32,                    // 初始垂直速度
+    pub angvel: f32,                // 初始角速度
 }
 
 // 小行星大小枚举
-#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum AsteroidSize {
     Big,
     Medium,
@@ -28,7 +31,16 @@ impl std::fmt::Display for AsteroidSize {
 }
 
 impl AsteroidSize {
-    // 根据大小返回摧毁该小行星获得的分数
+    pub const ALL: [AsteroidSize; 3] = [AsteroidSize::Big, AsteroidSize::Medium, AsteroidSize::Small];
+
+    // 按名字（不区分大小写，如 "big"）查找大小，用于调试命令
+    pub fn from_name(name: &str) -> Option<AsteroidSize> {
+        Self::ALL
+            .into_iter()
+            .find(|size| size.to_string().eq_ignore_ascii_case(name))
+    }
+
+    // 根据大小返回摧毁该小行星获得的基础分数，实际分数还要乘以材质倍数
     pub fn score(&self) -> u32 {
         match self {
             AsteroidSize::Big => 40,
@@ -37,21 +49,222 @@ impl AsteroidSize {
         }
     }
 
-    // 定义摧毁该大小小行星后是否分裂成更小的小行星
-    // 返回分裂后小行星大小及其生成半径
-    pub fn split(&self) -> Option<(AsteroidSize, f32)> {
+    // 该大小小行星的基础生命值，实际生命值还要乘以材质倍数
+    pub fn hit_points(&self) -> f32 {
+        match self {
+            AsteroidSize::Big => 3.0,
+            AsteroidSize::Medium => 2.0,
+            AsteroidSize::Small => 1.0,
+        }
+    }
+
+    // 按精灵大小估算的半径，精灵轮廓碰撞体尚未生成时也用它作为圆形碰撞体
+    pub fn radius(&self) -> f32 {
         match self {
-            AsteroidSize::Big => Some((AsteroidSize::Medium, 20.0)),
-            AsteroidSize::Medium => Some((AsteroidSize::Small, 10.0)),
-            AsteroidSize::Small => None, // 小的不会再分裂
+            AsteroidSize::Big => 101. / 2.0,
+            AsteroidSize::Medium => 43. / 2.0,
+            AsteroidSize::Small => 28. / 2.0,
         }
     }
+
+    // 精灵文件名中的大小后缀
+    fn sprite_suffix(&self) -> &'static str {
+        match self {
+            AsteroidSize::Big => "big1",
+            AsteroidSize::Medium => "med1",
+            AsteroidSize::Small => "small1",
+        }
+    }
+
+    // 摧毁该大小、该材质的小行星后是否分裂成更小的小行星
+    // 规则来自 SPLIT_TABLE，表中没有的组合不会分裂
+    pub fn split(&self, material: AsteroidMaterial) -> Option<SplitRule> {
+        SPLIT_TABLE
+            .iter()
+            .find(|rule| rule.material == material && rule.from == *self)
+            .copied()
+    }
 }
 
+// 小行星材质
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AsteroidMaterial {
+    Rock,      // 普通岩石
+    Iron,      // 铁质：需要多次命中
+    Ice,       // 冰质：碎成更多碎片
+    Explosive, // 爆炸性：摧毁时伤害周围
+    Crystal,   // 水晶：摧毁时掉落道具
+}
+
+impl std::fmt::Display for AsteroidMaterial {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+// 每种材质的属性
+#[derive(Debug, Copy, Clone)]
+pub struct MaterialProperties {
+    // 精灵文件名中的材质名，如 meteorIron_big1.png
+    pub sprite_name: &'static str,
+    // 分数倍数
+    pub score_factor: u32,
+    // 生命值倍数
+    pub health_factor: f32,
+    // 碰撞反弹系数
+    pub restitution: f32,
+    // 随机生成时的权重
+    pub spawn_weight: u32,
+}
+
+impl AsteroidMaterial {
+    pub const ALL: [AsteroidMaterial; 5] = [
+        AsteroidMaterial::Rock,
+        AsteroidMaterial::Iron,
+        AsteroidMaterial::Ice,
+        AsteroidMaterial::Explosive,
+        AsteroidMaterial::Crystal,
+    ];
+
+    pub fn properties(&self) -> MaterialProperties {
+        match self {
+            AsteroidMaterial::Rock => MaterialProperties {
+                sprite_name: "Brown",
+                score_factor: 1,
+                health_factor: 1.0,
+                restitution: 0.5,
+                spawn_weight: 55,
+            },
+            AsteroidMaterial::Iron => MaterialProperties {
+                sprite_name: "Iron",
+                score_factor: 3,
+                health_factor: 3.0,
+                restitution: 0.3,
+                spawn_weight: 15,
+            },
+            AsteroidMaterial::Ice => MaterialProperties {
+                sprite_name: "Ice",
+                score_factor: 1,
+                health_factor: 0.5,
+                restitution: 0.8,
+                spawn_weight: 15,
+            },
+            AsteroidMaterial::Explosive => MaterialProperties {
+                sprite_name: "Explosive",
+                score_factor: 2,
+                health_factor: 1.0,
+                restitution: 0.5,
+                spawn_weight: 8,
+            },
+            AsteroidMaterial::Crystal => MaterialProperties {
+                sprite_name: "Crystal",
+                score_factor: 4,
+                health_factor: 1.0,
+                restitution: 0.5,
+                spawn_weight: 7,
+            },
+        }
+    }
+
+    // 该材质、该大小的小行星精灵文件
+    pub fn sprite_path(&self, size: AsteroidSize) -> String {
+        format!(
+            "meteor{}_{}.png",
+            self.properties().sprite_name,
+            size.sprite_suffix()
+        )
+    }
+
+    // 按名字（不区分大小写，如 "iron"）查找材质，用于调试命令
+    pub fn from_name(name: &str) -> Option<AsteroidMaterial> {
+        Self::ALL
+            .into_iter()
+            .find(|material| material.to_string().eq_ignore_ascii_case(name))
+    }
+
+    // 按权重随机选择一种材质
+    pub fn random(rng: &mut impl Rng) -> AsteroidMaterial {
+        let total: u32 = Self::ALL.iter().map(|m| m.properties().spawn_weight).sum();
+        let mut roll = rng.gen_range(0..total);
+        for material in Self::ALL {
+            let weight = material.properties().spawn_weight;
+            if roll < weight {
+                return material;
+            }
+            roll -= weight;
+        }
+        AsteroidMaterial::Rock
+    }
+}
+
+// 分裂规则：某材质某大小的小行星被摧毁后，分裂成多少个什么大小的碎片
+#[derive(Debug, Copy, Clone)]
+pub struct SplitRule {
+    pub material: AsteroidMaterial,
+    pub from: AsteroidSize,
+    pub into: AsteroidSize,
+    pub radius: f32, // 碎片生成半径
+    pub count: u32,  // 碎片数量
+}
+
+// 分裂规则表，碎片沿用原小行星的材质
+// 爆炸性小行星不分裂，而是伤害周围的物体
+const SPLIT_TABLE: &[SplitRule] = &[
+    SplitRule { material: AsteroidMaterial::Rock, from: AsteroidSize::Big, into: AsteroidSize::Medium, radius: 20.0, count: 4 },
+    SplitRule { material: AsteroidMaterial::Rock, from: AsteroidSize::Medium, into: AsteroidSize::Small, radius: 10.0, count: 4 },
+    SplitRule { material: AsteroidMaterial::Iron, from: AsteroidSize::Big, into: AsteroidSize::Medium, radius: 20.0, count: 2 },
+    SplitRule { material: AsteroidMaterial::Iron, from: AsteroidSize::Medium, into: AsteroidSize::Small, radius: 10.0, count: 2 },
+    SplitRule { material: AsteroidMaterial::Ice, from: AsteroidSize::Big, into: AsteroidSize::Medium, radius: 20.0, count: 6 },
+    SplitRule { material: AsteroidMaterial::Ice, from: AsteroidSize::Medium, into: AsteroidSize::Small, radius: 10.0, count: 6 },
+    SplitRule { material: AsteroidMaterial::Crystal, from: AsteroidSize::Big, into: AsteroidSize::Medium, radius: 20.0, count: 3 },
+    SplitRule { material: AsteroidMaterial::Crystal, from: AsteroidSize::Medium, into: AsteroidSize::Small, radius: 10.0, count: 3 },
+];
+
+// 碎片化参数（资源）
+#[derive(Debug, Resource, Clone)]
+pub struct FragmentationConfig {
+    // 覆盖分裂规则表中的碎片数量，None 表示按规则表
+    pub fragment_count: Option<u32>,
+    // 半径为20的碎片向外散开的速度，碎片越小散得越快
+    pub spread_speed: f32,
+    // 散开方向偏向远离命中点的程度，0 表示均匀散开
+    pub impact_bias: f32,
+    // 命中冲量使母体（碎片和尘埃）整体获得的远离命中点的速度变化
+    pub impact_speed: f32,
+}
+
+impl Default for FragmentationConfig {
+    fn default() -> Self {
+        Self {
+            fragment_count: None,
+            spread_speed: 150.0,
+            impact_bias: 0.8,
+            impact_speed: 60.0,
+        }
+    }
+}
+
+// 爆炸性小行星的爆炸半径和对周围物体造成的伤害
+const EXPLOSIVE_RADIUS: f32 = 180.0;
+const EXPLOSIVE_DAMAGE: f32 = 2.0;
+
 // 小行星组件，绑定实体与大小
 #[derive(Component)]
 pub struct Asteroid {
     pub size: AsteroidSize,
+    pub material: AsteroidMaterial,
+    // 剩余生命值，降到0时小行星被摧毁
+    pub health: f32,
+}
+
+// 受伤闪白的持续时间（秒）
+const HIT_FLASH_SECS: f32 = 0.15;
+
+// 被击中但未摧毁的小行星短暂闪白
+#[derive(Component)]
+pub struct HitFlash {
+    pub timer: Timer,
 }
 
 // 小行星相关的插件，用于注册事件和系统
@@ -60,10 +273,21 @@ pub struct AsteroidPlugin;
 impl Plugin for AsteroidPlugin {
     fn build(&self, app: &mut App) {
         app.add_event::<AsteroidSpawnEvent>() // 注册小行星生成事件
+            .init_resource::<FragmentationConfig>()
+            // 受击闪烁虽然只是表现效果，但移除 HitFlash 会改变小行星在查询中的顺序，
+            // 必须按固定时间步进行，否则联机双方爆炸波及的先后（以及随机数的消耗顺序）可能不同
             .add_systems(
-                Update,
-                (arena_asteroids, spawn_asteroid_event) // 更新时处理小行星逻辑
+                FixedUpdate,
+                (arena_asteroids, asteroid_hit_flash) // 按固定时间步处理小行星生成计时和受击闪烁
                     .run_if(in_state(GameState::Running)), // 仅在游戏运行状态执行
+            )
+            // 生成事件在 FixedUpdate 的任何系统和受伤观察者中都可能写入（碎片、定时生成），
+            // 统一在物理步进之前处理，一步中产生的事件都在这一步生成，不会留到下一步
+            .add_systems(
+                FixedPostUpdate,
+                spawn_asteroid_event
+                    .before(PhysicsSet::Prepare)
+                    .run_if(in_state(GameState::Running)),
             );
     }
 }
@@ -73,42 +297,61 @@ fn spawn_asteroid_event(
     mut commands: Commands,
     mut event_reader: EventReader<AsteroidSpawnEvent>,
     handles: Res<SpriteAssets>, // 精灵资源句柄
+    sprite_colliders: Res<SpriteColliders>, // 按精灵轮廓生成的碰撞体
 ) {
     for event in event_reader.read() {
-        // 根据大小选择对应精灵和碰撞半径
-        let (sprite_handle, radius) = match event.size {
-            AsteroidSize::Big => (handles.meteor_big.clone(), 101. / 2.0),
-            AsteroidSize::Medium => (handles.meteor_med.clone(), 43. / 2.0),
-            AsteroidSize::Small => (handles.meteor_small.clone(), 28. / 2.0),
-        };
-        commands
-            .spawn((
-                Name::new(format!("Asteroid {}", event.size)), // 实体名字
-                Sprite {
-                    image: sprite_handle.clone(),
-                    ..default()
-                },
-                Transform::from_translation(Vec3::new(event.x, event.y, 1.0)), // 位置
-                Asteroid { size: event.size }, // 添加小行星组件
-                StateScoped(AppState::Game),   // 只在游戏状态可见
-                CollisionLayers::new(
-                    GameLayer::Asteroid, // 小行星的物理层
-                    [GameLayer::Asteroid, GameLayer::Player, GameLayer::Laser], // 碰撞检测对象
-                ),
-                RigidBody::Dynamic,           // 动态刚体，受物理影响
-                Collider::circle(radius),     // 碰撞体为圆形
-                Restitution::new(0.5),        // 碰撞反弹系数
-                LinearVelocity(Vec2::new(event.vx, event.vy)), // 初始线速度
-                AngularVelocity(event.angvel),                  // 初始角速度
-            ))
-            .observe(on_asteroid_damage); // 监听受伤事件触发分裂或销毁
+        spawn_asteroid(&mut commands, &handles, &sprite_colliders, event);
     }
 }
 
+// 生成一颗小行星实体，回滚和读档时也用它重建小行星
+pub fn spawn_asteroid(
+    commands: &mut Commands,
+    handles: &SpriteAssets,
+    sprite_colliders: &SpriteColliders,
+    event: &AsteroidSpawnEvent,
+) -> Entity {
+    // 根据材质和大小选择精灵，碰撞体使用精灵轮廓的凸包
+    // 精灵尚未加载完成时退回到按大小估算的圆形
+    let sprite_handle = handles.meteor(event.material, event.size);
+    let collider = sprite_colliders
+        .get(&sprite_handle)
+        .unwrap_or_else(|| Collider::circle(event.size.radius()));
+    let properties = event.material.properties();
+    commands
+        .spawn((
+            Name::new(format!("Asteroid {} {}", event.material, event.size)), // 实体名字
+            Sprite {
+                image: sprite_handle.clone(),
+                ..default()
+            },
+            Transform::from_translation(Vec3::new(event.x, event.y, 1.0)), // 位置
+            Asteroid {
+                size: event.size,
+                material: event.material,
+                health: event.size.hit_points() * properties.health_factor,
+            }, // 添加小行星组件
+            MinimapIcon::ASTEROID,         // 在小地图上显示
+            StateScoped(AppState::Game),   // 只在游戏状态可见
+            CollisionLayers::new(
+                GameLayer::Asteroid, // 小行星的物理层
+                [GameLayer::Asteroid, GameLayer::Player, GameLayer::Laser], // 碰撞检测对象
+            ),
+            RigidBody::Dynamic,           // 动态刚体，受物理影响
+            collider,                     // 碰撞体
+            Restitution::new(properties.restitution), // 碰撞反弹系数
+            LinearVelocity(Vec2::new(event.vx, event.vy)), // 初始线速度
+            AngularVelocity(event.angvel),                  // 初始角速度
+        ))
+        .observe(on_asteroid_damage) // 监听受伤事件触发分裂或销毁
+        .id()
+}
+
 // 负责定时生成新小行星
 fn arena_asteroids(
     time: Res<Time>,
     mut arena: ResMut<Arena>,
+    mut rng: ResMut<GameRng>,
     mut asteroid_spawn_events: EventWriter<AsteroidSpawnEvent>,
     asteroids: Query<&Asteroid>,
 ) {
@@ -124,27 +367,22 @@ fn arena_asteroids(
             );
             arena.asteroid_spawn_timer.set_duration(duration);
 
-            let mut rng = thread_rng();
+            let half_size = arena.size / 2.0;
             // 随机选择生成小行星的边界（0=顶部，1=左侧）
             let side = rng.gen_range(0..2u8);
             let (x, y) = match side {
-                0 => (
-                    rng.gen_range((-ARENA_WIDTH / 2.0)..(ARENA_WIDTH / 2.0)),
-                    ARENA_HEIGHT / 2.0,
-                ),
-                _ => (
-                    -ARENA_WIDTH / 2.0,
-                    rng.gen_range((-ARENA_HEIGHT / 2.0)..(ARENA_HEIGHT / 2.0)),
-                ),
+                0 => (rng.gen_range(-half_size.x..half_size.x), half_size.y),
+                _ => (-half_size.x, rng.gen_range(-half_size.y..half_size.y)),
             };
             // 随机初速度和角速度
             let vx = rng.gen_range((-ARENA_WIDTH / 4.0)..(ARENA_WIDTH / 4.0));
             let vy = rng.gen_range((-ARENA_HEIGHT / 4.0)..(ARENA_HEIGHT / 4.0));
             let angvel = rng.gen_range(-10.0..10.0);
 
-            // 触发小行星生成事件
+            // 触发小行星生成事件，材质按权重随机
             asteroid_spawn_events.write(AsteroidSpawnEvent {
                 size: AsteroidSize::Big,
+                material: AsteroidMaterial::random(&mut rng.0),
                 x,
                 y,
                 vx,
@@ -155,40 +393,131 @@ fn arena_asteroids(
     }
 }
 
+#[allow(clippy::type_complexity)]
 // 小行星受伤事件回调，负责处理分裂与销毁
 fn on_asteroid_damage(
     trigger: Trigger<Damage>,       // 触发事件的目标
     mut commands: Commands,          // 实体命令管理
     mut arena: ResMut<Arena>,        // 游戏状态资源
+    mut combo: ResMut<Combo>,        // 连击状态
+    mut score_events: EventWriter<ScoreEvent>, // 得分提示事件写入器
     mut asteroid_spawn_events: EventWriter<AsteroidSpawnEvent>, // 生成事件写入器
-    asteroids: Query<(&Asteroid, &Transform, &AngularVelocity)>, // 查询小行星相关组件
+    mut explosion_spawn_events: EventWriter<SpawnExplosionEvent>, // 爆炸事件写入器
+    mut pickup_spawn_events: EventWriter<PickupSpawnEvent>, // 道具生成事件写入器
+    mut background_flashes: EventWriter<BackgroundFlash>, // 背景闪光事件写入器
+    fragmentation: Res<FragmentationConfig>, // 碎片化参数
+    mut rng: ResMut<GameRng>,        // 游戏随机数生成器
+    mut asteroids: Query<(&mut Asteroid, &Transform, &LinearVelocity, &AngularVelocity)>, // 查询小行星相关组件
+    neighbours: Query<(Entity, &Transform, Has<Ship>), Or<(With<Asteroid>, With<Ship>)>>, // 爆炸波及范围内的物体
 ) {
     let asteroid_entity = trigger.target(); // 受伤的小行星实体
-    let (asteroid, asteroid_transform, asteroid_angvel) = asteroids.get(asteroid_entity).unwrap();
-
-    // 增加分数
-    arena.score += asteroid.size.score();
-
-    // 如果小行星能分裂，则生成4个更小的小行星
-    if let Some((size, radius)) = asteroid.size.split() {
-        let mut rng = thread_rng();
-        for i in 0..4 {
-            let x_pos = if i % 2 == 0 { 1. } else { -1. };
-            let y_pos = if (i / 2) % 2 == 0 { 1. } else { -1. };
-            let x = asteroid_transform.translation.x + x_pos * 1.5 * radius;
-            let y = asteroid_transform.translation.y + y_pos * 1.5 * radius;
-            let vx =
-                rng.gen_range((-ARENA_WIDTH / (radius / 4.))..(ARENA_WIDTH / (radius / 4.)));
-            let vy =
-                rng.gen_range((-ARENA_HEIGHT / (radius / 4.))..(ARENA_HEIGHT / (radius / 4.)));
+    let damage = trigger.event();
+    let Ok((mut asteroid, asteroid_transform, asteroid_linvel, asteroid_angvel)) =
+        asteroids.get_mut(asteroid_entity)
+    else {
+        return; // 同一帧内已被摧毁
+    };
+    if asteroid.health <= 0.0 {
+        return;
+    }
+
+    // 激光命中计入连击，其它伤害沿用当前倍率
+    let multiplier = match damage.source {
+        DamageSource::Laser => combo.hit(),
+        DamageSource::Impact | DamageSource::Explosion | DamageSource::GravityWell => {
+            combo.multiplier
+        }
+    };
 
+    // 扣除生命值，未摧毁时只闪白
+    asteroid.health -= damage.amount;
+    if asteroid.health > 0.0 {
+        commands.entity(asteroid_entity).insert(HitFlash {
+            timer: Timer::from_seconds(HIT_FLASH_SECS, TimerMode::Once),
+        });
+        return;
+    }
+
+    let position = asteroid_transform.translation.truncate();
+    let properties = asteroid.material.properties();
+
+    // 按连击倍率和材质倍数增加分数，并在小行星位置弹出得分提示
+    let points = asteroid.size.score() * properties.score_factor * multiplier;
+    arena.score += points;
+    score_events.write(ScoreEvent {
+        points,
+        x: position.x,
+        y: position.y,
+    });
+
+    // 按小行星大小生成碎片爆炸
+    explosion_spawn_events.write(SpawnExplosionEvent {
+        kind: ExplosionKind::AsteroidDestroyed(asteroid.size),
+        x: position.x,
+        y: position.y,
+    });
+
+    match asteroid.material {
+        // 爆炸性小行星伤害爆炸半径内的其它小行星和飞船
+        AsteroidMaterial::Explosive => {
+            background_flashes.write(BackgroundFlash {
+                color: LinearRgba::rgb(1.0, 0.5, 0.1),
+                strength: 0.6,
+            });
+            // 飞船受伤会中断连击，波及的小行星按当前倍率得分，触发顺序会影响得分；
+            // 按位置排序，不依赖查询的遍历顺序
+            let mut hit: Vec<_> = neighbours
+                .iter()
+                .map(|(entity, transform, is_ship)| {
+                    (entity, transform.translation.truncate(), is_ship)
+                })
+                .filter(|(entity, point, _)| {
+                    *entity != asteroid_entity && point.distance(position) < EXPLOSIVE_RADIUS
+                })
+                .collect();
+            hit.sort_by(|(_, a, _), (_, b, _)| a.x.total_cmp(&b.x).then(a.y.total_cmp(&b.y)));
+            for (entity, _, is_ship) in hit {
+                // 飞船被波及只损失一条命
+                commands.trigger_targets(
+                    Damage {
+                        amount: if is_ship { 1.0 } else { EXPLOSIVE_DAMAGE },
+                        source: DamageSource::Explosion,
+                        point: position,
+                    },
+                    entity,
+                );
+            }
+        }
+        // 水晶小行星掉落道具
+        AsteroidMaterial::Crystal => {
+            pickup_spawn_events.write(PickupSpawnEvent {
+                kind: PickupKind::random(&mut rng.0),
+                position,
+            });
+        }
+        _ => {}
+    }
+
+    // 按分裂规则生成更小的碎片
+    if let Some(rule) = asteroid.size.split(asteroid.material) {
+        let count = fragmentation.fragment_count.unwrap_or(rule.count);
+        for fragment in fragment_velocities(
+            &mut rng.0,
+            &fragmentation,
+            &rule,
+            count,
+            position,
+            asteroid_linvel.0,
+            damage.point,
+        ) {
             // 发送生成新小行星事件
             asteroid_spawn_events.write(AsteroidSpawnEvent {
-                size,
-                x,
-                y,
-                vx,
-                vy,
+                size: rule.into,
+                material: asteroid.material,
+                x: fragment.position.x,
+                y: fragment.position.y,
+                vx: fragment.velocity.x,
+                vy: fragment.velocity.y,
                 angvel: asteroid_angvel.0,
             });
         }
@@ -197,3 +526,126 @@ fn on_asteroid_damage(
     // 销毁当前受伤小行星实体
     commands.entity(asteroid_entity).despawn();
 }
+
+// 单个碎片的生成位置和速度
+struct Fragment {
+    position: Vec2,
+    velocity: Vec2,
+}
+
+// 计算碎片的位置和速度
+// 碎片均匀分布在原位置周围，继承母体速度，再叠加向外散开的速度；散开方向偏向远离命中点的一侧。
+// 同一次分裂的碎片质量相同，散开速度减去平均值后不改变碎片的总动量。
+// 碎片的总质量只占母体的一部分，其余当作尘埃，保持母体的速度飞散；命中冲量按质量分给碎片和尘埃，
+// 碎片整体获得 impact_speed 的速度变化。碎片数量再少也不会因为分到整个母体的动量而飞得更快
+fn fragment_velocities(
+    rng: &mut impl Rng,
+    config: &FragmentationConfig,
+    rule: &SplitRule,
+    count: u32,
+    center: Vec2,
+    parent_velocity: Vec2,
+    impact_point: Vec2,
+) -> Vec<Fragment> {
+    if count == 0 {
+        return Vec::new();
+    }
+    let away = (center - impact_point).try_normalize().unwrap_or(Vec2::Y);
+    let speed = config.spread_speed * 20.0 / rule.radius;
+    let start = rng.gen_range(0.0..std::f32::consts::TAU);
+
+    let mut fragments: Vec<Fragment> = (0..count)
+        .map(|i| {
+            let direction = Vec2::from_angle(start + i as f32 * std::f32::consts::TAU / count as f32);
+            let spread = (direction + away * config.impact_bias).normalize_or_zero()
+                * speed
+                * rng.gen_range(0.7..1.3);
+            Fragment {
+                position: center + direction * 1.5 * rule.radius * std::f32::consts::SQRT_2,
+                velocity: spread,
+            }
+        })
+        .collect();
+
+    let mean = fragments.iter().map(|f| f.velocity).sum::<Vec2>() / count as f32;
+    for fragment in fragments.iter_mut() {
+        fragment.velocity += parent_velocity - mean + away * config.impact_speed;
+    }
+    fragments
+}
+
+// 受伤闪白：精灵颜色从过曝的白色渐变回正常
+fn asteroid_hit_flash(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut asteroids: Query<(Entity, &mut HitFlash, &mut Sprite), With<Asteroid>>,
+) {
+    for (entity, mut flash, mut sprite) in asteroids.iter_mut() {
+        flash.timer.tick(time.delta());
+        if flash.timer.finished() {
+            sprite.color = Color::WHITE;
+            commands.entity(entity).remove::<HitFlash>();
+        } else {
+            let brightness = 1.0 + 3.0 * flash.timer.fraction_remaining();
+            sprite.color = Color::linear_rgb(brightness, brightness, brightness);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    use super::*;
+
+    fn mean_velocity(fragments: &[Fragment]) -> Vec2 {
+        fragments.iter().map(|fragment| fragment.velocity).sum::<Vec2>() / fragments.len() as f32
+    }
+
+    #[test]
+    fn fragments_keep_parent_velocity_plus_impulse() {
+        let mut rng = ChaCha8Rng::seed_from_u64(7);
+        let config = FragmentationConfig::default();
+        let center = Vec2::new(100.0, -50.0);
+        let impact_point = Vec2::new(80.0, -50.0);
+        let parent_velocity = Vec2::new(30.0, -12.0);
+        let expected = parent_velocity + Vec2::X * config.impact_speed;
+        for rule in SPLIT_TABLE {
+            let fragments =
+                fragment_velocities(&mut rng, &config, rule, rule.count, center, parent_velocity, impact_point);
+            assert_eq!(fragments.len(), rule.count as usize);
+            let mean = mean_velocity(&fragments);
+            assert!(mean.abs_diff_eq(expected, 1e-3), "{mean} != {expected}");
+            // 散开速度使碎片分别朝不同方向飞出
+            assert!(fragments
+                .iter()
+                .any(|fragment| fragment.velocity.distance(fragments[0].velocity) > 1.0));
+        }
+    }
+
+    #[test]
+    fn single_fragment_stays_near_parent_speed() {
+        let mut rng = ChaCha8Rng::seed_from_u64(11);
+        let config = FragmentationConfig {
+            fragment_count: Some(1),
+            ..default()
+        };
+        let rule = SPLIT_TABLE[0];
+        let parent_velocity = Vec2::new(-40.0, 25.0);
+        let fragments = fragment_velocities(
+            &mut rng,
+            &config,
+            &rule,
+            config.fragment_count.unwrap(),
+            Vec2::ZERO,
+            parent_velocity,
+            Vec2::new(0.0, -30.0),
+        );
+        let [fragment] = fragments.as_slice() else {
+            panic!("expected one fragment, got {}", fragments.len());
+        };
+        // 只比母体多出命中带来的速度变化
+        assert!(fragment.velocity.distance(parent_velocity) <= config.impact_speed + 1e-3);
+    }
+}
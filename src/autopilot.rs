@@ -0,0 +1,325 @@
+use std::time::Duration;
+
+use crate::prelude::*;
+
+// 引力井影响半径内的这一比例视为危险区，AI 会主动离开
+const WELL_DANGER_FACTOR: f32 = 0.5;
+// 转向时允许的角度误差（弧度），小于它时不再按旋转键
+const STEER_DEADBAND: f32 = 0.02;
+// 开火时允许的最小瞄准误差（弧度），远处的小目标也按这个角度判断
+const MIN_FIRE_TOLERANCE: f32 = 0.03;
+
+// AI 驾驶员组件：挂在飞船上后，AI 每个固定时间步写入该飞船的 ActionState<PlayerAction>，
+// 与玩家按键的效果完全相同，飞船的移动和开火仍由 ship_input_system 处理
+#[derive(Component, Debug, Clone)]
+pub struct AiPilot {
+    /// 只对这个距离内的小行星做躲避判断
+    pub sight_radius: f32,
+    /// 小行星预计从这个距离内（再加上小行星半径）经过时开始躲避
+    pub avoid_radius: f32,
+    /// 躲避判断向前预测的时间（秒）
+    pub lookahead_secs: f32,
+    /// 目标比这个距离远时才推进靠近
+    pub engage_range: f32,
+    /// 速度超过该值后不再推进
+    pub cruise_speed: f32,
+}
+
+impl Default for AiPilot {
+    fn default() -> Self {
+        Self {
+            sight_radius: 400.0,
+            avoid_radius: 60.0,
+            lookahead_secs: 1.2,
+            engage_range: 350.0,
+            cruise_speed: 220.0,
+        }
+    }
+}
+
+// AI 驾驶设置（资源），通过命令行参数开启
+#[derive(Resource, Debug, Default, Clone)]
+pub struct AutopilotConfig {
+    /// 由 AI 驾驶玩家1的飞船（--autopilot）
+    pub player: bool,
+    /// 额外生成一艘由 AI 驾驶的僚机，玩家ID为2（--ai-partner）
+    pub partner: bool,
+    /// 压力测试：AI 驾驶并自动开始游戏，结束后自动重开并打印每局统计（--soak [局数]）
+    /// Some(0) 表示一直运行
+    pub soak_games: Option<u32>,
+    /// 不创建窗口、不渲染（--headless），一般与 --soak 一起使用
+    pub headless: bool,
+}
+
+// 压力测试统计（资源）
+#[derive(Resource, Debug, Default)]
+struct SoakStats {
+    // 已完成的局数
+    games: u32,
+    // 所有局的最高分
+    best_score: u32,
+}
+
+// AI 驾驶插件：为飞船挂上 AI 驾驶员、生成 AI 僚机，并支持无人值守的压力测试
+pub struct AutopilotPlugin;
+
+impl Plugin for AutopilotPlugin {
+    fn build(&self, app: &mut App) {
+        let soak = |config: Res<AutopilotConfig>| config.soak_games.is_some();
+        app.init_resource::<AutopilotConfig>()
+            .init_resource::<SoakStats>()
+            .add_systems(OnEnter(GameState::Setup), spawn_partner)
+            .add_systems(
+                FixedUpdate,
+                (attach_player_autopilot, autopilot_system)
+                    .chain()
+                    .before(ship_input_system)
+                    .run_if(in_state(GameState::Running)),
+            )
+            .add_systems(Update, soak_start_game.run_if(in_state(AppState::Menu).and(soak)))
+            .add_systems(OnEnter(GameState::Over), soak_game_over.run_if(soak));
+    }
+}
+
+// 设置要求时，让本地玩家的飞船由 AI 驾驶（联机时本地玩家可能是玩家2）
+fn attach_player_autopilot(
+    mut commands: Commands,
+    config: Res<AutopilotConfig>,
+    net: Option<Res<NetSession>>,
+    ships: Query<(Entity, &Ship), Added<Ship>>,
+) {
+    if !config.player {
+        return;
+    }
+    let local_player = net.map_or(1, |net| net.local_player());
+    for (entity, ship) in ships.iter() {
+        if ship.player_id == local_player {
+            commands.entity(entity).insert(AiPilot::default());
+        }
+    }
+}
+
+// 设置要求时，生成由 AI 驾驶的玩家2僚机
+fn spawn_partner(mut commands: Commands, config: Res<AutopilotConfig>, handles: Res<SpriteAssets>) {
+    if !config.partner {
+        return;
+    }
+    let ship = spawn_player_ship(&mut commands, &handles, 2, ship_spawn_point(2));
+    commands.entity(ship).insert(AiPilot::default());
+}
+
+// 本步的操作决定
+struct Maneuver {
+    // 希望朝向的方向
+    heading: Vec2,
+    // 是否推进
+    thrust: bool,
+}
+
+#[allow(clippy::type_complexity)]
+// AI 驾驶：先躲避即将撞上来的小行星和引力井，否则追踪最近的小行星并带提前量射击
+pub fn autopilot_system(
+    arena: Res<Arena>,
+    asteroids: Query<(&Position, &LinearVelocity, &Asteroid)>,
+    wells: Query<(&GravityWell, &Transform)>,
+    mut pilots: Query<(
+        &AiPilot,
+        &mut ActionState<PlayerAction>,
+        &Position,
+        &Rotation,
+        &LinearVelocity,
+        Has<Respawning>,
+    )>,
+) {
+    for (pilot, mut action_state, position, rotation, linvel, respawning) in pilots.iter_mut() {
+        for action in [
+            PlayerAction::Forward,
+            PlayerAction::RotateLeft,
+            PlayerAction::RotateRight,
+            PlayerAction::Fire,
+        ] {
+            action_state.release(&action);
+        }
+        if respawning {
+            continue;
+        }
+
+        let forward = *rotation * Vec2::Y;
+        let maneuver = evade(pilot, arena.size, position.0, linvel.0, &asteroids, &wells)
+            .or_else(|| engage(pilot, arena.size, position.0, linvel.0, forward, &asteroids))
+            .unwrap_or(Maneuver {
+                heading: forward,
+                thrust: false,
+            });
+
+        // 朝目标方向转向，正角度为逆时针（向左）
+        let angle = forward.angle_to(maneuver.heading);
+        if angle > STEER_DEADBAND {
+            action_state.press(&PlayerAction::RotateLeft);
+        } else if angle < -STEER_DEADBAND {
+            action_state.press(&PlayerAction::RotateRight);
+        }
+        if maneuver.thrust {
+            action_state.press(&PlayerAction::Forward);
+        }
+        if should_fire(arena.size, position.0, linvel.0, forward, &asteroids) {
+            action_state.press(&PlayerAction::Fire);
+        }
+    }
+}
+
+// 躲避：找出最先撞上来的小行星，朝远离最近接近点的方向逃开；
+// 进入引力井危险区时直接背离井心
+fn evade(
+    pilot: &AiPilot,
+    arena_size: Vec2,
+    ship_position: Vec2,
+    ship_velocity: Vec2,
+    asteroids: &Query<(&Position, &LinearVelocity, &Asteroid)>,
+    wells: &Query<(&GravityWell, &Transform)>,
+) -> Option<Maneuver> {
+    for (well, transform) in wells.iter() {
+        let offset = wrapped_offset(transform.translation.truncate() - ship_position, arena_size);
+        if offset.length() < well.radius * WELL_DANGER_FACTOR {
+            return Some(Maneuver {
+                heading: -offset.normalize_or(Vec2::Y),
+                thrust: true,
+            });
+        }
+    }
+
+    let mut most_urgent: Option<(f32, Vec2)> = None;
+    for (position, linvel, asteroid) in asteroids.iter() {
+        let offset = wrapped_offset(position.0 - ship_position, arena_size);
+        if offset.length() > pilot.sight_radius {
+            continue;
+        }
+        // 相对运动下的最近接近时间和位置
+        let relative_velocity = linvel.0 - ship_velocity;
+        let time = (-offset.dot(relative_velocity) / relative_velocity.length_squared().max(1.0))
+            .clamp(0.0, pilot.lookahead_secs);
+        let closest = offset + relative_velocity * time;
+        if closest.length() > pilot.avoid_radius + asteroid.size.radius() {
+            continue;
+        }
+        if most_urgent.is_none_or(|(urgent_time, _)| time < urgent_time) {
+            // 正面对撞时最近点就在飞船上，改为向垂直于相对速度的方向躲开
+            let escape = (-closest)
+                .try_normalize()
+                .unwrap_or_else(|| relative_velocity.perp().normalize_or(Vec2::X));
+            most_urgent = Some((time, escape));
+        }
+    }
+    most_urgent.map(|(_, escape)| Maneuver {
+        heading: escape,
+        thrust: true,
+    })
+}
+
+// 追击：转向最近小行星的拦截方向，离得远或速度太慢时推进靠近
+fn engage(
+    pilot: &AiPilot,
+    arena_size: Vec2,
+    ship_position: Vec2,
+    ship_velocity: Vec2,
+    forward: Vec2,
+    asteroids: &Query<(&Position, &LinearVelocity, &Asteroid)>,
+) -> Option<Maneuver> {
+    let (offset, velocity) = asteroids
+        .iter()
+        .map(|(position, linvel, _)| (wrapped_offset(position.0 - ship_position, arena_size), linvel.0))
+        .min_by(|(a, _), (b, _)| a.length_squared().total_cmp(&b.length_squared()))?;
+    let heading = lead_direction(offset, velocity, ship_velocity);
+    let thrust = offset.length() > pilot.engage_range
+        && ship_velocity.length() < pilot.cruise_speed
+        && forward.dot(heading) > 0.9;
+    Some(Maneuver { heading, thrust })
+}
+
+// 当前朝向上是否能打中某颗小行星（考虑提前量和激光射程）
+fn should_fire(
+    arena_size: Vec2,
+    ship_position: Vec2,
+    ship_velocity: Vec2,
+    forward: Vec2,
+    asteroids: &Query<(&Position, &LinearVelocity, &Asteroid)>,
+) -> bool {
+    let range = LASER_SPEED * LASER_LIFETIME_SECS;
+    asteroids.iter().any(|(position, linvel, asteroid)| {
+        let offset = wrapped_offset(position.0 - ship_position, arena_size);
+        let distance = offset.length();
+        if distance > range {
+            return false;
+        }
+        let aim = lead_direction(offset, linvel.0, ship_velocity);
+        let tolerance = (asteroid.size.radius() / distance.max(1.0)).max(MIN_FIRE_TOLERANCE);
+        forward.angle_to(aim).abs() < tolerance
+    })
+}
+
+// 计算射击提前量：激光继承飞船的竖直速度（见 spawn_laser），
+// 求解激光与目标相遇的最早时间，返回此时应瞄准的方向；追不上时直接瞄准目标
+fn lead_direction(offset: Vec2, target_velocity: Vec2, ship_velocity: Vec2) -> Vec2 {
+    let velocity = target_velocity - ship_velocity * Vec2::Y;
+    // |offset + velocity * t| = LASER_SPEED * t
+    let a = velocity.length_squared() - LASER_SPEED * LASER_SPEED;
+    let b = 2.0 * offset.dot(velocity);
+    let c = offset.length_squared();
+    let time = if a.abs() < f32::EPSILON {
+        -c / b
+    } else {
+        let discriminant = b * b - 4.0 * a * c;
+        if discriminant < 0.0 {
+            -1.0
+        } else {
+            let root = discriminant.sqrt();
+            let (t1, t2) = ((-b - root) / (2.0 * a), (-b + root) / (2.0 * a));
+            match (t1 > 0.0, t2 > 0.0) {
+                (true, true) => t1.min(t2),
+                (true, false) => t1,
+                (false, true) => t2,
+                (false, false) => -1.0,
+            }
+        }
+    };
+    let aim = if time > 0.0 {
+        offset + velocity * time
+    } else {
+        offset
+    };
+    aim.normalize_or(Vec2::Y)
+}
+
+// 压力测试：回到主菜单后立即开始新的一局
+fn soak_start_game(mut game_mode: ResMut<GameMode>, mut next_app_state: ResMut<NextState<AppState>>) {
+    *game_mode = GameMode::Classic;
+    next_app_state.set(AppState::Game);
+}
+
+// 压力测试：一局结束时打印统计，达到局数后退出，否则回到主菜单重开
+fn soak_game_over(
+    config: Res<AutopilotConfig>,
+    time: Res<Time<Real>>,
+    arena: Res<Arena>,
+    wave: Option<Res<Wave>>,
+    mut stats: ResMut<SoakStats>,
+    mut next_app_state: ResMut<NextState<AppState>>,
+    mut app_exit_events: EventWriter<AppExit>,
+) {
+    stats.games += 1;
+    stats.best_score = stats.best_score.max(arena.score);
+    info!(
+        "Soak game {}: score {}, wave {}, best {}, uptime {:?}",
+        stats.games,
+        arena.score,
+        wave.map_or(0, |wave| wave.index + 1),
+        stats.best_score,
+        Duration::from_secs(time.elapsed().as_secs()),
+    );
+    match config.soak_games {
+        Some(games) if games > 0 && stats.games >= games => {
+            app_exit_events.write(AppExit::Success);
+        }
+        _ => next_app_state.set(AppState::Menu),
+    }
+}
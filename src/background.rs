@@ -1,9 +1,16 @@
 use bevy::render::render_resource::{AsBindGroup, ShaderRef};
-use bevy::sprite::Material2d;
+use bevy::sprite::{AlphaMode2d, Material2d};
 use bevy::sprite::Material2dPlugin;
 
 use crate::prelude::*;
 
+// 主题切换时背景参数每秒向目标值逼近的速率
+const THEME_TRANSITION_RATE: f32 = 1.5;
+// 闪光每秒衰减到原来的比例
+const FLASH_DECAY: f32 = 0.02;
+// 没有飞船时（例如在菜单中）视差星空的缓慢漂移速度
+const PARALLAX_IDLE_VELOCITY: Vec2 = Vec2::new(40.0, 20.0);
+
 // 定义背景插件，负责在游戏初始化时生成背景并持续更新背景时间
 pub struct BackgroundPlugin;
 
@@ -11,34 +18,179 @@ impl Plugin for BackgroundPlugin {
     fn build(&self, app: &mut App) {
         // 注册自定义材质插件，用于渲染背景
         app.add_plugins(Material2dPlugin::<BackgroundMaterial>::default())
+            .add_plugins(Material2dPlugin::<StarLayerMaterial>::default())
+            .add_event::<BackgroundFlash>()
+            .init_resource::<CurrentBackgroundTheme>()
+            .init_resource::<ParallaxConfig>()
             // 在游戏进入 Setup 状态时生成背景实体
             .add_systems(OnEnter(AppState::Setup), spawn_background)
-            // 每帧更新背景材质中的时间变量，用于动态效果
-            .add_systems(Update, update_background_time);
+            // 不同游戏阶段使用不同的主题，游戏进行中的主题由当前波次决定
+            .add_systems(OnEnter(AppState::Menu), set_theme(BackgroundTheme::CLASSIC))
+            .add_systems(OnEnter(AppState::Credits), set_theme(BackgroundTheme::AURORA))
+            .add_systems(OnEnter(GameState::Over), set_theme(BackgroundTheme::CRIMSON))
+            // 每帧更新背景材质中的时间变量，用于动态效果；同时过渡主题参数、处理闪光
+            .add_systems(
+                Update,
+                (
+                    update_background_time,
+                    flash_on_explosion,
+                    update_background_theme,
+                )
+                    .chain(),
+            )
+            // 视差配置或游戏模式变化时（包括首次插入）重建星空层，之后每帧根据飞船运动移动各层
+            .add_systems(
+                Update,
+                (
+                    rebuild_parallax_layers.run_if(
+                        resource_changed::<ParallaxConfig>.or(resource_changed::<GameMode>),
+                    ),
+                    update_parallax_layers,
+                )
+                    .chain(),
+            )
+            // 游戏模式变化时让背景覆盖新的竞技场大小
+            .add_systems(Update, resize_background.run_if(resource_changed::<GameMode>));
     }
 }
 
+/// 背景主题，控制星空着色器的各项参数
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BackgroundTheme {
+    /// 星星密度，越大视野中的星星越多
+    pub star_density: f32,
+    /// 近处星云颜色
+    pub nebula_near: LinearRgba,
+    /// 远处星云颜色
+    pub nebula_far: LinearRgba,
+    /// 星空滚动速度
+    pub scroll_velocity: Vec2,
+    /// 整体亮度
+    pub intensity: f32,
+}
+
+impl BackgroundTheme {
+    /// 经典星空，即最初的背景效果
+    pub const CLASSIC: Self = Self {
+        star_density: 1.0,
+        nebula_near: LinearRgba::rgb(1.0, 1.0, 1.0),
+        nebula_far: LinearRgba::rgb(1.0, 1.0, 1.0),
+        scroll_velocity: Vec2::new(2.0, 1.0),
+        intensity: 1.0,
+    };
+    /// 深空：星星稀疏，偏蓝，缓慢漂移
+    pub const DEEP_SPACE: Self = Self {
+        star_density: 0.7,
+        nebula_near: LinearRgba::rgb(0.6, 0.8, 1.2),
+        nebula_far: LinearRgba::rgb(0.3, 0.4, 1.0),
+        scroll_velocity: Vec2::new(1.0, 0.5),
+        intensity: 0.8,
+    };
+    /// 极光：青绿与紫色星云，星星密集
+    pub const AURORA: Self = Self {
+        star_density: 1.3,
+        nebula_near: LinearRgba::rgb(0.4, 1.2, 0.9),
+        nebula_far: LinearRgba::rgb(0.9, 0.4, 1.2),
+        scroll_velocity: Vec2::new(-1.5, 1.5),
+        intensity: 1.1,
+    };
+    /// 赤红：用于游戏结束等紧张场景
+    pub const CRIMSON: Self = Self {
+        star_density: 1.0,
+        nebula_near: LinearRgba::rgb(1.4, 0.5, 0.4),
+        nebula_far: LinearRgba::rgb(1.0, 0.2, 0.2),
+        scroll_velocity: Vec2::new(0.5, 0.25),
+        intensity: 0.9,
+    };
+}
+
+/// 当前背景主题，修改后背景会平滑过渡到新主题
+#[derive(Resource, Debug, Clone, Copy, PartialEq)]
+pub struct CurrentBackgroundTheme(pub BackgroundTheme);
+
+impl Default for CurrentBackgroundTheme {
+    fn default() -> Self {
+        Self(BackgroundTheme::CLASSIC)
+    }
+}
+
+/// 背景闪光事件，用于让背景对游戏事件做出反应（例如大型小行星被摧毁）
+#[derive(Event, Debug, Clone, Copy)]
+pub struct BackgroundFlash {
+    pub color: LinearRgba,
+    /// 闪光强度，0~1
+    pub strength: f32,
+}
+
+// 返回一个把当前主题设置为指定主题的系统
+fn set_theme(theme: BackgroundTheme) -> impl FnMut(ResMut<CurrentBackgroundTheme>) {
+    move |mut current: ResMut<CurrentBackgroundTheme>| {
+        current.0 = theme;
+    }
+}
+
+// 背景实体标记组件
+#[derive(Component)]
+struct Background;
+
 // 生成背景实体，使用一个拉伸的矩形网格和自定义背景材质
 fn spawn_background(
     mut commands: Commands,
     mut meshes: ResMut<Assets<Mesh>>,
     mut materials: ResMut<Assets<BackgroundMaterial>>,
+    theme: Res<CurrentBackgroundTheme>,
+    mode: Res<GameMode>,
 ) {
     commands.spawn((
+        Background,
         // 创建一个矩形网格（默认单位矩形）
         Mesh2d(meshes.add(Rectangle::default())),
-        // 缩放矩形以覆盖整个游戏区域（ARENA_WIDTH x ARENA_HEIGHT）
-        Transform::from_scale(Vec3::new(ARENA_WIDTH, ARENA_HEIGHT, 1.0)),
-        // 应用自定义背景材质，初始时间为0
-        MeshMaterial2d(materials.add(BackgroundMaterial { time: 0.0 })),
+        // 缩放矩形以覆盖整个游戏区域
+        Transform::from_scale(mode.arena_size().extend(1.0)),
+        // 应用自定义背景材质，初始时间为0，参数取自当前主题
+        MeshMaterial2d(materials.add(BackgroundMaterial::new(&theme.0))),
     ));
 }
 
-// 自定义背景材质结构体，包含一个uniform变量time，表示经过时间
+// 自定义背景材质结构体，所有字段合并为着色器中的同一个uniform结构
+// 字段顺序必须与 background.wgsl 中的 BackgroundMaterial 保持一致
 #[derive(Asset, AsBindGroup, Debug, Clone, TypePath)]
 struct BackgroundMaterial {
     #[uniform(0)]
     time: f32,
+    #[uniform(0)]
+    star_density: f32,
+    #[uniform(0)]
+    intensity: f32,
+    #[uniform(0)]
+    flash: f32,
+    // 星空累计滚动的距离，由 scroll_velocity 逐帧积分，避免改变速度时画面跳变
+    #[uniform(0)]
+    scroll_offset: Vec2,
+    #[uniform(0)]
+    nebula_near: LinearRgba,
+    #[uniform(0)]
+    nebula_far: LinearRgba,
+    #[uniform(0)]
+    flash_color: LinearRgba,
+    // 当前滚动速度，只在CPU端使用
+    scroll_velocity: Vec2,
+}
+
+impl BackgroundMaterial {
+    fn new(theme: &BackgroundTheme) -> Self {
+        Self {
+            time: 0.0,
+            star_density: theme.star_density,
+            intensity: theme.intensity,
+            flash: 0.0,
+            scroll_offset: Vec2::ZERO,
+            nebula_near: theme.nebula_near,
+            nebula_far: theme.nebula_far,
+            flash_color: LinearRgba::WHITE,
+            scroll_velocity: theme.scroll_velocity,
+        }
+    }
 }
 
 // 实现Material2d trait，指定使用的片段着色器文件为 background.wgsl
@@ -48,6 +200,13 @@ impl Material2d for BackgroundMaterial {
     }
 }
 
+// 按当前游戏模式的竞技场大小缩放背景
+fn resize_background(mode: Res<GameMode>, mut backgrounds: Query<&mut Transform, With<Background>>) {
+    for mut transform in backgrounds.iter_mut() {
+        transform.scale = mode.arena_size().extend(1.0);
+    }
+}
+
 // 每帧更新背景材质的time字段，用来驱动shader的动画
 fn update_background_time(
     time: Res<Time>,
@@ -58,6 +217,218 @@ fn update_background_time(
     if state.is_none() || state.unwrap().get() != &GameState::Paused {
         for (_, background) in backgrounds.iter_mut() {
             background.time += time.delta_secs();
+            background.scroll_offset += background.scroll_velocity * time.delta_secs();
+        }
+    }
+}
+
+// 大型小行星被摧毁或飞船坠毁时让背景闪一下
+fn flash_on_explosion(
+    mut explosion_events: EventReader<SpawnExplosionEvent>,
+    mut flash_events: EventWriter<BackgroundFlash>,
+) {
+    for event in explosion_events.read() {
+        match event.kind {
+            ExplosionKind::AsteroidDestroyed(AsteroidSize::Big) => {
+                flash_events.write(BackgroundFlash {
+                    color: LinearRgba::rgb(1.0, 0.9, 0.7),
+                    strength: 0.5,
+                });
+            }
+            ExplosionKind::ShipDead => {
+                flash_events.write(BackgroundFlash {
+                    color: LinearRgba::rgb(1.0, 0.3, 0.2),
+                    strength: 1.0,
+                });
+            }
+            _ => {}
+        }
+    }
+}
+
+// 让背景参数平滑过渡到当前主题，并处理闪光的触发与衰减
+fn update_background_theme(
+    time: Res<Time>,
+    theme: Res<CurrentBackgroundTheme>,
+    mut flash_events: EventReader<BackgroundFlash>,
+    mut backgrounds: ResMut<Assets<BackgroundMaterial>>,
+) {
+    let dt = time.delta_secs();
+    let t = (THEME_TRANSITION_RATE * dt).min(1.0);
+    let theme = &theme.0;
+    // 同一帧内多次闪光取最强的一次
+    let flash = flash_events
+        .read()
+        .max_by(|a, b| a.strength.total_cmp(&b.strength))
+        .copied();
+    for (_, background) in backgrounds.iter_mut() {
+        background.star_density = background.star_density.lerp(theme.star_density, t);
+        background.intensity = background.intensity.lerp(theme.intensity, t);
+        background.scroll_velocity = background.scroll_velocity.lerp(theme.scroll_velocity, t);
+        background.nebula_near = background.nebula_near.mix(&theme.nebula_near, t);
+        background.nebula_far = background.nebula_far.mix(&theme.nebula_far, t);
+
+        background.flash *= FLASH_DECAY.powf(dt);
+        if let Some(flash) = flash {
+            if flash.strength >= background.flash {
+                background.flash = flash.strength;
+                background.flash_color = flash.color;
+            }
+        }
+    }
+}
+
+/// 一层视差星空的参数
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ParallaxLayer {
+    /// 相对飞船运动的移动比例，越大表示越近
+    pub speed: f32,
+    /// 每个网格单元出现星星的概率，0~1
+    pub density: f32,
+    /// 星星半径（像素）
+    pub star_size: f32,
+    /// 星星亮度，0~1
+    pub brightness: f32,
+    /// 网格单元大小（像素），每个单元最多一颗星星
+    pub cell_size: f32,
+}
+
+/// 视差星空配置，层数和各层速度可在运行时修改，修改后星空层会重建
+#[derive(Resource, Debug, Clone, PartialEq)]
+pub struct ParallaxConfig {
+    /// 从远到近排列的星空层
+    pub layers: Vec<ParallaxLayer>,
+}
+
+impl Default for ParallaxConfig {
+    fn default() -> Self {
+        Self {
+            layers: vec![
+                ParallaxLayer {
+                    speed: 0.05,
+                    density: 0.3,
+                    star_size: 1.0,
+                    brightness: 0.5,
+                    cell_size: 40.0,
+                },
+                ParallaxLayer {
+                    speed: 0.12,
+                    density: 0.2,
+                    star_size: 1.5,
+                    brightness: 0.7,
+                    cell_size: 60.0,
+                },
+                ParallaxLayer {
+                    speed: 0.25,
+                    density: 0.15,
+                    star_size: 2.0,
+                    brightness: 0.9,
+                    cell_size: 90.0,
+                },
+            ],
+        }
+    }
+}
+
+// 视差星空层组件，记录该层的移动比例
+#[derive(Component)]
+struct ParallaxStars {
+    speed: f32,
+}
+
+// 视差星空层材质，在着色器中按网格随机生成星星
+// 字段顺序必须与 starfield.wgsl 中的 StarLayerMaterial 保持一致
+#[derive(Asset, AsBindGroup, Debug, Clone, TypePath)]
+struct StarLayerMaterial {
+    // 星空层累计偏移（像素）
+    #[uniform(0)]
+    offset: Vec2,
+    // 星空层尺寸（像素）
+    #[uniform(0)]
+    size: Vec2,
+    #[uniform(0)]
+    density: f32,
+    #[uniform(0)]
+    star_size: f32,
+    #[uniform(0)]
+    brightness: f32,
+    #[uniform(0)]
+    cell_size: f32,
+    // 随机种子，使各层星星分布不同
+    #[uniform(0)]
+    seed: f32,
+}
+
+// 星空层是半透明的，叠加在背景之上
+impl Material2d for StarLayerMaterial {
+    fn fragment_shader() -> ShaderRef {
+        "starfield.wgsl".into()
+    }
+
+    fn alpha_mode(&self) -> AlphaMode2d {
+        AlphaMode2d::Blend
+    }
+}
+
+// 按配置重新生成所有视差星空层
+fn rebuild_parallax_layers(
+    mut commands: Commands,
+    config: Res<ParallaxConfig>,
+    mode: Res<GameMode>,
+    layers: Query<Entity, With<ParallaxStars>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StarLayerMaterial>>,
+) {
+    for entity in layers.iter() {
+        commands.entity(entity).despawn();
+    }
+    let mesh = meshes.add(Rectangle::default());
+    let size = mode.arena_size();
+    for (i, layer) in config.layers.iter().enumerate() {
+        commands.spawn((
+            Name::new(format!("ParallaxStars {i}")),
+            Mesh2d(mesh.clone()),
+            // 位于背景（z=0）之上、游戏实体（z>=1）之下，越近的层越靠前
+            Transform::from_xyz(0.0, 0.0, 0.1 + 0.8 * i as f32 / config.layers.len() as f32)
+                .with_scale(size.extend(1.0)),
+            MeshMaterial2d(materials.add(StarLayerMaterial {
+                offset: Vec2::ZERO,
+                size,
+                density: layer.density,
+                star_size: layer.star_size,
+                brightness: layer.brightness,
+                cell_size: layer.cell_size,
+                seed: i as f32 * 17.0,
+            })),
+            ParallaxStars { speed: layer.speed },
+        ));
+    }
+}
+
+// 根据飞船速度移动各星空层，近处的层移动得更快，从而产生纵深感
+fn update_parallax_layers(
+    time: Res<Time>,
+    state: Option<Res<State<GameState>>>,
+    ships: Query<&LinearVelocity, With<Ship>>,
+    layers: Query<(&ParallaxStars, &MeshMaterial2d<StarLayerMaterial>)>,
+    mut materials: ResMut<Assets<StarLayerMaterial>>,
+) {
+    // 与背景时间一致，暂停时星空层也保持静止
+    if state.is_some_and(|state| state.get() == &GameState::Paused) {
+        return;
+    }
+    // 多艘飞船时取平均速度，没有飞船时缓慢漂移
+    let (sum, count) = ships
+        .iter()
+        .fold((Vec2::ZERO, 0), |(sum, count), linvel| (sum + linvel.0, count + 1));
+    let velocity = if count > 0 {
+        sum / count as f32
+    } else {
+        PARALLAX_IDLE_VELOCITY
+    };
+    for (stars, material) in layers.iter() {
+        if let Some(material) = materials.get_mut(&material.0) {
+            material.offset += velocity * stars.speed * time.delta_secs();
         }
     }
 }
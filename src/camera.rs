@@ -0,0 +1,169 @@
+use bevy::render::camera::{ScalingMode, Viewport};
+use bevy::window::{PrimaryWindow, WindowMode};
+
+use crate::prelude::*;
+
+// 主摄像机标记组件
+#[derive(Component)]
+pub struct MainCamera;
+
+// 摄像机插件：负责生成摄像机，让固定大小的竞技场适配任意窗口尺寸，并在大地图中跟随飞船
+pub struct CameraPlugin;
+
+impl Plugin for CameraPlugin {
+    fn build(&self, app: &mut App) {
+        // 进入Setup状态时执行摄像机初始化
+        app.add_systems(OnEnter(AppState::Setup), setup_camera)
+            // 窗口大小或游戏模式变化时重新计算视口；F11 切换全屏
+            .add_systems(
+                Update,
+                (
+                    (fit_camera_to_window, follow_ships).chain(),
+                    toggle_fullscreen,
+                ),
+            );
+    }
+}
+
+// 摄像机跟随的平滑系数，越大跟得越紧
+const CAMERA_FOLLOW_RESPONSE: f32 = 4.0;
+// 多架飞船同屏时，画面边缘至少保留的距离
+const CAMERA_FRAME_MARGIN: f32 = 200.0;
+
+// 摄像机初始化，生成一个2D摄像机实体
+fn setup_camera(mut commands: Commands) {
+    commands.spawn((Name::new("Camera"), Camera2d, MainCamera));
+}
+
+// 让竞技场按原始比例缩放到窗口中，多余的区域留出黑边（letterbox）
+// 摄像机投影固定为视野的逻辑大小（一般就是整个竞技场），视口则根据窗口的物理像素居中计算；
+// HUD 布局在视口内进行，UI 缩放比例随视口高度变化，保证文字大小与画面一致
+fn fit_camera_to_window(
+    windows: Query<&Window, With<PrimaryWindow>>,
+    mode: Res<GameMode>,
+    mut cameras: Query<(&mut Camera, &mut Projection), With<MainCamera>>,
+    mut ui_scale: ResMut<UiScale>,
+) {
+    let Ok(window) = windows.single() else {
+        return;
+    };
+    let view = mode.view_size();
+    let window_size = window.physical_size().as_vec2();
+    if window_size.x <= 0.0 || window_size.y <= 0.0 {
+        return; // 窗口最小化
+    }
+
+    let scale = (window_size.x / view.x).min(window_size.y / view.y);
+    let size = (view * scale).floor().max(Vec2::ONE);
+    let position = ((window_size - size) / 2.0).floor();
+    let viewport = Viewport {
+        physical_position: position.as_uvec2(),
+        physical_size: size.as_uvec2(),
+        ..default()
+    };
+
+    for (mut camera, mut projection) in cameras.iter_mut() {
+        let unchanged = camera.viewport.as_ref().is_some_and(|current| {
+            current.physical_position == viewport.physical_position
+                && current.physical_size == viewport.physical_size
+        });
+        if !unchanged {
+            camera.viewport = Some(viewport.clone());
+        }
+        let scaling_mode = ScalingMode::Fixed {
+            width: view.x,
+            height: view.y,
+        };
+        match projection.as_mut() {
+            Projection::Orthographic(ortho) => {
+                // 只替换缩放方式，保留跟随系统设置的缩放倍数
+                let unchanged = matches!(
+                    ortho.scaling_mode,
+                    ScalingMode::Fixed { width, height } if width == view.x && height == view.y
+                );
+                if !unchanged {
+                    ortho.scaling_mode = scaling_mode;
+                }
+            }
+            _ => {
+                *projection = Projection::Orthographic(OrthographicProjection {
+                    scaling_mode,
+                    ..OrthographicProjection::default_2d()
+                });
+            }
+        }
+    }
+
+    // HUD 按默认窗口高度设计，视口变化时等比缩放
+    let scale = size.y / window.scale_factor() / ARENA_HEIGHT;
+    if (ui_scale.0 - scale).abs() > f32::EPSILON {
+        ui_scale.0 = scale;
+    }
+}
+
+#[allow(clippy::type_complexity)]
+// 大地图模式下摄像机跟随飞船（观战时跟随画面中的飞船），等待复活的飞船不计入；多架飞船时取它们的中心，必要时拉远镜头把所有飞船框进画面
+// 摄像机不会越过竞技场边界，竞技场不超过一屏时始终停在中心
+fn follow_ships(
+    time: Res<Time>,
+    mode: Res<GameMode>,
+    ships: Query<&Transform, (Or<(With<Ship>, With<SpectatedShip>)>, Without<Respawning>, Without<MainCamera>)>,
+    mut cameras: Query<(&mut Transform, &mut Projection), With<MainCamera>>,
+) {
+    let arena = mode.arena_size();
+    let view = mode.view_size();
+
+    // 所有飞船的包围盒
+    let mut bounds: Option<Rect> = None;
+    for transform in ships.iter() {
+        let point = transform.translation.truncate();
+        bounds = Some(match bounds {
+            Some(rect) => rect.union_point(point),
+            None => Rect::from_center_size(point, Vec2::ZERO),
+        });
+    }
+    let (target, target_zoom) = match bounds {
+        Some(rect) if mode.is_scrolling() => {
+            let needed = (rect.size() + CAMERA_FRAME_MARGIN * 2.0) / view;
+            let max_zoom = (arena / view).min_element();
+            (rect.center(), needed.max_element().clamp(1.0, max_zoom))
+        }
+        _ => (Vec2::ZERO, 1.0),
+    };
+
+    let t = 1.0 - (-CAMERA_FOLLOW_RESPONSE * time.delta_secs()).exp();
+    for (mut transform, mut projection) in cameras.iter_mut() {
+        let Projection::Orthographic(ortho) = projection.as_mut() else {
+            continue;
+        };
+        ortho.scale += (target_zoom - ortho.scale) * t;
+
+        // 飞船穿越边界被传送到另一侧时直接跳过去，而不是横扫整个竞技场
+        let current = transform.translation.truncate();
+        let mut center = if current.distance(target) > view.max_element() {
+            target
+        } else {
+            current.lerp(target, t)
+        };
+        let limit = ((arena - view * ortho.scale) / 2.0).max(Vec2::ZERO);
+        center = center.clamp(-limit, limit);
+        transform.translation.x = center.x;
+        transform.translation.y = center.y;
+    }
+}
+
+// 按 F11 在窗口模式和无边框全屏之间切换
+fn toggle_fullscreen(
+    keys: Res<ButtonInput<KeyCode>>,
+    mut windows: Query<&mut Window, With<PrimaryWindow>>,
+) {
+    if !keys.just_pressed(KeyCode::F11) {
+        return;
+    }
+    if let Ok(mut window) = windows.single_mut() {
+        window.mode = match window.mode {
+            WindowMode::Windowed => WindowMode::BorderlessFullscreen(MonitorSelection::Current),
+            _ => WindowMode::Windowed,
+        };
+    }
+}
@@ -0,0 +1,88 @@
+use crate::prelude::*;
+
+// 连击窗口：两次命中间隔超过该时间则连击中断
+const COMBO_WINDOW_SECS: f32 = 2.0;
+// 每连续命中多少次倍率加一
+const HITS_PER_MULTIPLIER: u32 = 3;
+// 最大倍率
+const MAX_MULTIPLIER: u32 = 8;
+
+// 连击状态（资源），记录连续命中次数和当前得分倍率
+#[derive(Debug, Resource)]
+pub struct Combo {
+    // 当前连击中的命中次数
+    pub hits: u32,
+    // 当前得分倍率
+    pub multiplier: u32,
+    // 距离连击中断的剩余时间
+    pub window: Timer,
+}
+
+impl Default for Combo {
+    fn default() -> Self {
+        Self {
+            hits: 0,
+            multiplier: 1,
+            window: Timer::from_seconds(COMBO_WINDOW_SECS, TimerMode::Once),
+        }
+    }
+}
+
+impl Combo {
+    // 记录一次命中并返回本次命中使用的倍率
+    pub fn hit(&mut self) -> u32 {
+        self.hits += 1;
+        self.multiplier = (1 + self.hits / HITS_PER_MULTIPLIER).min(MAX_MULTIPLIER);
+        self.window.reset();
+        self.multiplier
+    }
+
+    // 连击中断（未命中、受伤或超时），倍率回到1
+    pub fn reset(&mut self) {
+        if self.hits > 0 {
+            *self = Combo::default();
+        }
+    }
+}
+
+// 得分事件，在得分位置弹出“+N”提示
+#[derive(Event)]
+pub struct ScoreEvent {
+    // 本次获得的分数（已乘以倍率）
+    pub points: u32,
+    // 得分的位置
+    pub x: f32,
+    pub y: f32,
+}
+
+// 连击插件，负责连击计时和在每局开始时重置
+pub struct ComboPlugin;
+
+impl Plugin for ComboPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Combo>()
+            .add_event::<ScoreEvent>()
+            .add_systems(OnEnter(GameState::Setup), reset_combo)
+            .add_systems(
+                FixedUpdate,
+                combo_timer_system.run_if(in_state(GameState::Running)),
+            );
+    }
+}
+
+// 新的一局开始时清空连击
+fn reset_combo(mut combo: ResMut<Combo>) {
+    *combo = Combo::default();
+}
+
+// 连击计时，超过窗口时间没有命中则中断连击
+fn combo_timer_system(time: Res<Time>, mut combo: ResMut<Combo>) {
+    if combo.hits == 0 {
+        return; // 没有连击时不修改资源，避免HUD每帧刷新
+    }
+    // 计时本身不算连击变化，不触发变更检测
+    combo.bypass_change_detection().window.tick(time.delta());
+    if combo.window.finished() {
+        combo.reset();
+    }
+}
@@ -0,0 +1,490 @@
+use std::any::TypeId;
+use std::str::FromStr;
+
+use bevy::ecs::system::SystemParam;
+use bevy::input::keyboard::{Key, KeyboardInput};
+use bevy::input::ButtonState;
+
+use crate::prelude::*;
+
+// 控制台保留并显示的输出行数
+const CONSOLE_LOG_LINES: usize = 14;
+// 保留的历史命令条数
+const CONSOLE_HISTORY_LEN: usize = 50;
+
+// 按 Tab 可以补全的命令短语
+const CONSOLE_COMPLETIONS: &[&str] = &[
+    "help",
+    "clear",
+    "spawn asteroid big",
+    "spawn asteroid medium",
+    "spawn asteroid small",
+    "explode",
+    "god",
+    "give life",
+    "wave",
+    "timescale",
+    "seed",
+    "toggle colliders",
+    "theme classic",
+    "theme deep_space",
+    "theme aurora",
+    "theme crimson",
+    "state menu",
+    "state game",
+    "state paused",
+];
+
+// help 命令输出的说明
+const CONSOLE_HELP: &[&str] = &[
+    "spawn asteroid <big|medium|small> <x> <y> [material]",
+    "explode <x> <y>",
+    "god [on|off]          give life [n]",
+    "wave <n>              timescale <factor>",
+    "seed [n]              toggle colliders",
+    "theme <classic|deep_space|aurora|crimson>",
+    "state <menu|game|paused>    clear",
+];
+
+// 开发者控制台（资源）
+// 按 ` 键打开或关闭，输入作弊和调试命令，上下方向键翻阅历史命令，Tab 补全
+#[derive(Resource, Debug, Default)]
+pub struct Console {
+    /// 控制台是否打开
+    pub open: bool,
+    // 正在输入的命令
+    input: String,
+    // 输出内容
+    log: Vec<String>,
+    // 历史命令，最新的在最后
+    history: Vec<String>,
+    // 正在翻阅的历史命令下标
+    history_index: Option<usize>,
+    // 已提交、等待执行的命令
+    pending: Vec<String>,
+}
+
+impl Console {
+    // 追加一行输出，超出显示行数时丢弃最早的内容
+    fn print(&mut self, line: impl Into<String>) {
+        self.log.push(line.into());
+        let overflow = self.log.len().saturating_sub(CONSOLE_LOG_LINES);
+        self.log.drain(..overflow);
+    }
+
+    // 提交当前输入的命令
+    fn submit(&mut self) {
+        let line = std::mem::take(&mut self.input).trim().to_string();
+        self.history_index = None;
+        if line.is_empty() {
+            return;
+        }
+        self.print(format!("> {line}"));
+        if self.history.last() != Some(&line) {
+            self.history.push(line.clone());
+            let overflow = self.history.len().saturating_sub(CONSOLE_HISTORY_LEN);
+            self.history.drain(..overflow);
+        }
+        self.pending.push(line);
+    }
+
+    // 翻阅历史命令，back 为 true 时向更早的命令翻
+    fn browse_history(&mut self, back: bool) {
+        if self.history.is_empty() {
+            return;
+        }
+        self.history_index = match (self.history_index, back) {
+            (None, true) => Some(self.history.len() - 1),
+            (None, false) => None,
+            (Some(index), true) => Some(index.saturating_sub(1)),
+            (Some(index), false) if index + 1 < self.history.len() => Some(index + 1),
+            (Some(_), false) => None,
+        };
+        self.input = self
+            .history_index
+            .map_or_else(String::new, |index| self.history[index].clone());
+    }
+
+    // 用命令短语补全当前输入：唯一匹配时直接补全，多个匹配时补全公共前缀并列出候选
+    fn autocomplete(&mut self) {
+        let input = self.input.to_lowercase();
+        let candidates: Vec<&str> = CONSOLE_COMPLETIONS
+            .iter()
+            .copied()
+            .filter(|phrase| phrase.starts_with(&input) && phrase.len() > input.len())
+            .collect();
+        match candidates.as_slice() {
+            [] => {}
+            [phrase] => self.input = format!("{phrase} "),
+            [first, rest @ ..] => {
+                let common = rest.iter().fold(first.len(), |len, phrase| {
+                    first
+                        .bytes()
+                        .zip(phrase.bytes())
+                        .take(len)
+                        .take_while(|(a, b)| a == b)
+                        .count()
+                });
+                self.input = first[..common].to_string();
+                self.print(candidates.join("  "));
+            }
+        }
+    }
+}
+
+// 控制台界面根节点
+#[derive(Component)]
+struct ConsoleRoot;
+
+// 控制台输出文本
+#[derive(Component)]
+struct ConsoleLogText;
+
+// 控制台输入行文本
+#[derive(Component)]
+struct ConsoleInputText;
+
+// 开发者控制台插件，调试版本或带 --console 参数运行时启用
+pub struct ConsolePlugin;
+
+impl Plugin for ConsolePlugin {
+    fn build(&self, app: &mut App) {
+        let console_open = |console: Res<Console>| console.open;
+        app.init_resource::<Console>().add_systems(
+            Update,
+            (
+                toggle_console,
+                console_input_system,
+                (
+                    block_game_input,
+                    run_console_commands,
+                    update_console_text,
+                )
+                    .chain()
+                    .run_if(console_open),
+            )
+                .chain(),
+        );
+    }
+}
+
+// 按 ` 键打开或关闭控制台
+fn toggle_console(
+    mut commands: Commands,
+    keys: Res<ButtonInput<KeyCode>>,
+    assets: Option<Res<UiAssets>>,
+    mut console: ResMut<Console>,
+    roots: Query<Entity, With<ConsoleRoot>>,
+    mut ship_actions: Query<&mut ActionState<PlayerAction>, With<InputMap<PlayerAction>>>,
+    menu_actions: Option<ResMut<ActionState<MenuAction>>>,
+) {
+    if !keys.just_pressed(KeyCode::Backquote) {
+        return;
+    }
+    let Some(assets) = assets else {
+        return;
+    };
+    console.open = !console.open;
+    if console.open {
+        spawn_console(&mut commands, &assets);
+        return;
+    }
+    for entity in roots.iter() {
+        commands.entity(entity).despawn();
+    }
+    // 关闭后恢复游戏输入
+    for mut action_state in ship_actions.iter_mut() {
+        action_state.enable();
+    }
+    if let Some(mut menu_actions) = menu_actions {
+        menu_actions.enable();
+    }
+}
+
+// 生成屏幕上方的控制台界面
+fn spawn_console(commands: &mut Commands, assets: &UiAssets) {
+    let text_font = TextFont {
+        font: assets.font_fira.clone(),
+        font_size: 18.0,
+        ..default()
+    };
+    commands.spawn((
+        ConsoleRoot,
+        Node {
+            position_type: PositionType::Absolute,
+            top: Val::Px(0.0),
+            left: Val::Px(0.0),
+            width: Val::Percent(100.0),
+            flex_direction: FlexDirection::Column,
+            padding: UiRect::all(Val::Px(8.0)),
+            ..default()
+        },
+        BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.8)),
+        GlobalZIndex(20), // 盖在演示遮罩之上
+        children![
+            (
+                ConsoleLogText,
+                Text::default(),
+                text_font.clone(),
+                TextColor(Color::srgb(0.7, 0.7, 0.7)),
+            ),
+            (
+                ConsoleInputText,
+                Text::new("> _"),
+                text_font,
+                TextColor(Color::srgb(0.0, 0.9, 0.9)),
+            ),
+        ],
+    ));
+}
+
+// 控制台打开时屏蔽键盘操作的飞船和菜单输入，避免输入命令时飞船也跟着动
+fn block_game_input(
+    mut ship_actions: Query<&mut ActionState<PlayerAction>, With<InputMap<PlayerAction>>>,
+    menu_actions: Option<ResMut<ActionState<MenuAction>>>,
+) {
+    for mut action_state in ship_actions.iter_mut() {
+        if !action_state.disabled() {
+            action_state.disable();
+        }
+    }
+    if let Some(mut menu_actions) = menu_actions {
+        if !menu_actions.disabled() {
+            menu_actions.disable();
+        }
+    }
+}
+
+// 处理控制台的键盘输入
+fn console_input_system(mut events: EventReader<KeyboardInput>, mut console: ResMut<Console>) {
+    // 控制台关闭时丢弃按键，避免打开时把之前游戏中的按键也当作输入
+    if !console.open {
+        events.clear();
+        return;
+    }
+    for event in events.read() {
+        if event.state != ButtonState::Pressed {
+            continue;
+        }
+        match &event.logical_key {
+            Key::Enter => console.submit(),
+            Key::Backspace => {
+                console.input.pop();
+            }
+            Key::Escape => console.input.clear(),
+            Key::ArrowUp => console.browse_history(true),
+            Key::ArrowDown => console.browse_history(false),
+            Key::Tab => console.autocomplete(),
+            Key::Space => console.input.push(' '),
+            Key::Character(text) => {
+                // ` 是开关控制台的按键，不输入
+                let text: String = text.chars().filter(|c| *c != '`' && !c.is_control()).collect();
+                console.input.push_str(&text);
+            }
+            _ => {}
+        }
+    }
+}
+
+// 控制台命令能影响的游戏状态
+#[derive(SystemParam)]
+struct ConsoleTargets<'w, 's> {
+    game_state: Option<Res<'w, State<GameState>>>,
+    next_app_state: ResMut<'w, NextState<AppState>>,
+    next_game_state: ResMut<'w, NextState<GameState>>,
+    asteroid_spawn_events: EventWriter<'w, AsteroidSpawnEvent>,
+    explosion_spawn_events: EventWriter<'w, SpawnExplosionEvent>,
+    start_wave_events: EventWriter<'w, StartWave>,
+    wave: Option<Res<'w, Wave>>,
+    god_mode: ResMut<'w, GodMode>,
+    game_seed: ResMut<'w, GameSeed>,
+    time: ResMut<'w, Time<Virtual>>,
+    gizmo_store: Option<ResMut<'w, GizmoConfigStore>>,
+    theme: ResMut<'w, CurrentBackgroundTheme>,
+    ships: Query<'w, 's, &'static mut Ship>,
+}
+
+impl ConsoleTargets<'_, '_> {
+    fn require_running(&self) -> Result<(), String> {
+        if self
+            .game_state
+            .as_ref()
+            .is_some_and(|state| *state.get() == GameState::Running)
+        {
+            Ok(())
+        } else {
+            Err("game is not running".to_string())
+        }
+    }
+
+    // 执行一条命令，返回要显示的结果
+    fn execute(&mut self, words: &[&str]) -> Result<String, String> {
+        match words {
+            ["spawn", "asteroid", size, x, y, rest @ ..] => {
+                let size = AsteroidSize::from_name(size).ok_or(format!("unknown size `{size}`"))?;
+                let material = match rest {
+                    [] => AsteroidMaterial::Rock,
+                    [name] => AsteroidMaterial::from_name(name)
+                        .ok_or(format!("unknown material `{name}`"))?,
+                    _ => return Err("too many arguments".to_string()),
+                };
+                let (x, y) = (parse_arg::<f32>(x)?, parse_arg::<f32>(y)?);
+                self.require_running()?;
+                self.asteroid_spawn_events.write(AsteroidSpawnEvent {
+                    size,
+                    material,
+                    x,
+                    y,
+                    vx: 0.0,
+                    vy: 0.0,
+                    angvel: 0.0,
+                });
+                Ok(format!("spawned {material} {size} asteroid at ({x}, {y})"))
+            }
+            ["explode", x, y] => {
+                let (x, y) = (parse_arg::<f32>(x)?, parse_arg::<f32>(y)?);
+                self.require_running()?;
+                self.explosion_spawn_events.write(SpawnExplosionEvent {
+                    kind: ExplosionKind::ShipDead,
+                    x,
+                    y,
+                });
+                Ok(format!("explosion at ({x}, {y})"))
+            }
+            ["god"] | ["god", "on" | "off"] => {
+                self.god_mode.0 = match words {
+                    [_, "on"] => true,
+                    [_, "off"] => false,
+                    _ => !self.god_mode.0,
+                };
+                Ok(format!("god mode {}", if self.god_mode.0 { "on" } else { "off" }))
+            }
+            ["give", "life", rest @ ..] => {
+                let amount = match rest {
+                    [] => 1,
+                    [amount] => parse_arg::<u32>(amount)?,
+                    _ => return Err("too many arguments".to_string()),
+                };
+                let mut ship = self
+                    .ships
+                    .iter_mut()
+                    .find(|ship| ship.player_id == 1)
+                    .ok_or("no player ship")?;
+                ship.life += amount;
+                Ok(format!("life: {}", ship.life))
+            }
+            ["wave", number] => {
+                let number = parse_arg::<usize>(number)?;
+                if !(1..=WAVES.len()).contains(&number) {
+                    return Err(format!("wave must be in 1..={}", WAVES.len()));
+                }
+                self.require_running()?;
+                if self.wave.is_none() {
+                    return Err("no wave in progress".to_string());
+                }
+                self.start_wave_events.write(StartWave(number - 1));
+                Ok(format!("starting wave {number}"))
+            }
+            ["timescale", factor] => {
+                let factor = parse_arg::<f32>(factor)?;
+                if !(factor > 0.0 && factor <= 10.0) {
+                    return Err("timescale must be in (0, 10]".to_string());
+                }
+                self.time.set_relative_speed(factor);
+                Ok(format!("timescale {factor}"))
+            }
+            ["seed"] => Ok(match self.game_seed.0 {
+                Some(seed) => format!("seed {seed} (used by the next game)"),
+                None => "random seed".to_string(),
+            }),
+            ["seed", seed] => {
+                self.game_seed.0 = Some(parse_arg::<u64>(seed)?);
+                Ok(format!("seed {seed} will be used by the next game"))
+            }
+            ["toggle", "colliders"] => {
+                // 物理调试图形只在调试版本中注册
+                let config = self
+                    .gizmo_store
+                    .as_mut()
+                    .and_then(|store| store.get_config_mut_dyn(&TypeId::of::<PhysicsGizmos>()))
+                    .map(|(config, _)| config)
+                    .ok_or("physics debug rendering is not available in this build")?;
+                config.enabled = !config.enabled;
+                Ok(format!("colliders {}", if config.enabled { "shown" } else { "hidden" }))
+            }
+            ["theme", name] => {
+                self.theme.0 = match *name {
+                    "classic" => BackgroundTheme::CLASSIC,
+                    "deep_space" => BackgroundTheme::DEEP_SPACE,
+                    "aurora" => BackgroundTheme::AURORA,
+                    "crimson" => BackgroundTheme::CRIMSON,
+                    _ => return Err(format!("unknown theme `{name}`")),
+                };
+                Ok(format!("theme {name}"))
+            }
+            ["state", "menu"] => {
+                self.next_app_state.set(AppState::Menu);
+                Ok("back to menu".to_string())
+            }
+            ["state", "game"] => {
+                match self.game_state.as_ref().map(|state| *state.get()) {
+                    None => self.next_app_state.set(AppState::Game),
+                    Some(GameState::Paused) => self.next_game_state.set(GameState::Running),
+                    Some(_) => {}
+                }
+                Ok("game".to_string())
+            }
+            ["state", "paused"] => {
+                self.require_running()?;
+                self.next_game_state.set(GameState::Paused);
+                Ok("paused".to_string())
+            }
+            _ => Err(format!("unknown command `{}`, try `help`", words.join(" "))),
+        }
+    }
+}
+
+// 解析命令参数
+fn parse_arg<T: FromStr>(text: &str) -> Result<T, String> {
+    text.parse().map_err(|_| format!("invalid argument `{text}`"))
+}
+
+// 执行已提交的命令，并把结果写到控制台输出
+fn run_console_commands(mut console: ResMut<Console>, mut targets: ConsoleTargets) {
+    if console.pending.is_empty() {
+        return;
+    }
+    for line in std::mem::take(&mut console.pending) {
+        let line = line.to_lowercase();
+        let words: Vec<&str> = line.split_whitespace().collect();
+        match words.as_slice() {
+            ["help"] => {
+                for help in CONSOLE_HELP {
+                    console.print(*help);
+                }
+            }
+            ["clear"] => console.log.clear(),
+            _ => match targets.execute(&words) {
+                Ok(message) => console.print(message),
+                Err(error) => console.print(format!("error: {error}")),
+            },
+        }
+    }
+}
+
+// 更新控制台的输出和输入行
+fn update_console_text(
+    console: Res<Console>,
+    mut log_text: Query<&mut Text, (With<ConsoleLogText>, Without<ConsoleInputText>)>,
+    mut input_text: Query<&mut Text, (With<ConsoleInputText>, Without<ConsoleLogText>)>,
+) {
+    if !console.is_changed() {
+        return;
+    }
+    for mut text in log_text.iter_mut() {
+        text.0 = console.log.join("\n");
+    }
+    for mut text in input_text.iter_mut() {
+        text.0 = format!("> {}_", console.input);
+    }
+}
@@ -1,10 +1,23 @@
 use crate::prelude::*;
 
 /// 爆炸类型枚举，表示不同的爆炸效果
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
 pub enum ExplosionKind {
-    ShipDead,         // 飞船死亡爆炸
-    ShipContact,      // 飞船接触碰撞爆炸
-    LaserOnAsteroid,  // 激光击中小行星爆炸
+    ShipDead,                       // 飞船死亡爆炸
+    ShipContact,                    // 飞船接触碰撞爆炸
+    LaserOnAsteroid,                // 激光击中小行星爆炸
+    AsteroidDestroyed(AsteroidSize), // 小行星被摧毁时的碎片爆炸
+}
+
+/// 爆炸特效的表现方式
+///
+/// 粒子插件启用时切换为 `Particles`，碎片、火花和烟雾由粒子系统生成；
+/// 在 WASM 等未启用粒子插件的平台上保持 `Sprites`，退回到精灵缩放动画
+#[derive(Resource, Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub enum ExplosionStyle {
+    #[default]
+    Sprites,
+    Particles,
 }
 
 /// 生成爆炸事件，携带爆炸类型和位置坐标
@@ -32,9 +45,11 @@ impl Plugin for ExplosionPlugin {
         app
             // 注册爆炸生成事件
             .add_event::<SpawnExplosionEvent>()
-            // 注册两个系统：动画播放和事件响应，只在游戏运行状态执行
+            // 默认使用精灵爆炸，粒子插件会将其覆盖为粒子爆炸
+            .init_resource::<ExplosionStyle>()
+            // 注册两个系统：动画播放和事件响应，只在游戏运行状态按固定时间步执行
             .add_systems(
-                Update,
+                FixedUpdate,
                 (animate_explosion, catch_explosion_event).run_if(in_state(GameState::Running)),
             );
     }
@@ -46,6 +61,7 @@ fn catch_explosion_event(
     mut event_reader: EventReader<SpawnExplosionEvent>,
     handles: Res<SpriteAssets>, // 纹理资源句柄
     audios: Res<AudioAssets>,   // 音频资源句柄
+    style: Res<ExplosionStyle>, // 爆炸表现方式
 ) {
     for event in event_reader.read() {
         // 根据爆炸类型匹配不同的纹理、音效、大小、缩放和持续时间参数
@@ -71,8 +87,20 @@ fn catch_explosion_event(
                 1.5,    // 放大1.5倍
                 1.,     // 持续1秒
             ),
+            // 小行星碎片只由粒子系统表现，精灵模式下激光命中的闪光已足够
+            ExplosionKind::AsteroidDestroyed(_) => continue,
         };
 
+        // 粒子模式下精灵动画由粒子碎片代替，只保留爆炸音效
+        if *style == ExplosionStyle::Particles {
+            commands.spawn((
+                AudioPlayer(sound),
+                PlaybackSettings::DESPAWN, // 播放结束后自动销毁
+                StateScoped(AppState::Game),
+            ));
+            continue;
+        }
+
         // 生成爆炸实体，附加纹理、位置、爆炸组件、音效等
         commands.spawn((
             Sprite {
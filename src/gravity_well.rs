@@ -0,0 +1,161 @@
+use crate::prelude::*;
+
+// 引力井组件：对影响半径内的飞船、激光和小行星施加指向中心的平方反比引力，
+// 进入核心的物体会被吞噬
+#[derive(Component, Debug, Clone, Copy)]
+pub struct GravityWell {
+    // 引力强度，距离 d 处的加速度为 strength / d²
+    pub strength: f32,
+    // 影响半径
+    pub radius: f32,
+    // 核心半径，进入后被吞噬；也是计算引力时的最小距离，避免引力无穷大
+    pub core_radius: f32,
+}
+
+// 引力井核心的旋转速度（弧度/秒）
+const WELL_SPIN_SPEED: f32 = 1.5;
+
+// 受引力井影响的物体
+type Attracted = Or<(With<Ship>, With<Laser>, With<Asteroid>)>;
+
+// 引力井插件，负责施加引力、吞噬物体和绘制影响范围
+pub struct GravityWellPlugin;
+
+impl Plugin for GravityWellPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            FixedUpdate,
+            (
+                // 飞船的推力每步由输入系统重新设置，引力要在它之后叠加
+                gravity_well_forces.after(ship_input_system),
+                gravity_well_swallow,
+            )
+                .run_if(in_state(GameState::Running)),
+        )
+        .add_systems(Update, draw_gravity_wells.run_if(in_state(GameState::Running)));
+    }
+}
+
+// 在指定位置生成一个引力井
+pub fn spawn_gravity_well(
+    commands: &mut Commands,
+    handles: &SpriteAssets,
+    well: GravityWell,
+    position: Vec2,
+) {
+    commands.spawn((
+        Name::new("GravityWell"),
+        Sprite {
+            image: handles.pickup.clone(),
+            custom_size: Some(Vec2::splat(well.core_radius * 2.0)),
+            color: Color::srgb(0.25, 0.05, 0.35),
+            ..default()
+        },
+        Transform::from_translation(position.extend(0.5)),
+        well,
+        MinimapIcon::GRAVITY_WELL,
+        StateScoped(AppState::Game),
+    ));
+}
+
+#[allow(clippy::type_complexity)]
+// 计算每个物体受到的合引力并写入 ExternalForce
+// 激光和小行星没有其它外力来源，直接覆盖；飞船在推力基础上叠加
+// 质量取自碰撞体：ComputedMass 要到物理引擎的准备阶段才更新，刚生成的物体在第一步里还是零，
+// 联机回滚时重新生成的飞船会因此比原地恢复的飞船少受一步引力
+fn gravity_well_forces(
+    mut commands: Commands,
+    wells: Query<(&GravityWell, &Transform)>,
+    mut bodies: Query<
+        (
+            Entity,
+            &Position,
+            &ColliderMassProperties,
+            Option<&mut ExternalForce>,
+            Has<Ship>,
+        ),
+        (Attracted, Without<RigidBodyDisabled>),
+    >,
+) {
+    for (entity, position, mass, force, is_ship) in bodies.iter_mut() {
+        let mut total = Vec2::ZERO;
+        for (well, transform) in wells.iter() {
+            let offset = transform.translation.truncate() - position.0;
+            let distance = offset.length();
+            if distance < well.radius {
+                let distance = distance.max(well.core_radius);
+                total += offset.normalize_or_zero() * well.strength * mass.mass / (distance * distance);
+            }
+        }
+        match force {
+            Some(mut force) if is_ship => {
+                force.apply_force(total);
+            }
+            Some(mut force) if force.force() != total => {
+                force.set_force(total);
+            }
+            Some(_) => {}
+            None if total != Vec2::ZERO => {
+                commands.entity(entity).insert(ExternalForce::new(total));
+            }
+            None => {}
+        }
+    }
+}
+
+#[allow(clippy::type_complexity)]
+// 进入核心的物体被吞噬：激光和小行星直接消失，飞船受到伤害
+fn gravity_well_swallow(
+    mut commands: Commands,
+    mut explosion_spawn_events: EventWriter<SpawnExplosionEvent>,
+    wells: Query<(&GravityWell, &Transform)>,
+    bodies: Query<(Entity, &Position, Has<Ship>), (Attracted, Without<RigidBodyDisabled>)>,
+) {
+    for (well, transform) in wells.iter() {
+        let center = transform.translation.truncate();
+        for (entity, position, is_ship) in bodies.iter() {
+            if position.0.distance(center) > well.core_radius {
+                continue;
+            }
+            if is_ship {
+                commands.trigger_targets(
+                    Damage {
+                        amount: 1.0,
+                        source: DamageSource::GravityWell,
+                        point: center,
+                    },
+                    entity,
+                );
+            } else {
+                explosion_spawn_events.write(SpawnExplosionEvent {
+                    kind: ExplosionKind::LaserOnAsteroid,
+                    x: position.x,
+                    y: position.y,
+                });
+                commands.entity(entity).despawn();
+            }
+        }
+    }
+}
+
+// 核心缓慢旋转，并画出引力井的影响范围
+fn draw_gravity_wells(
+    time: Res<Time>,
+    mut gizmos: Gizmos,
+    mut wells: Query<(&GravityWell, &mut Transform)>,
+) {
+    for (well, mut transform) in wells.iter_mut() {
+        transform.rotate_z(WELL_SPIN_SPEED * time.delta_secs());
+        let center = transform.translation.truncate();
+        gizmos.circle_2d(
+            Isometry2d::from_translation(center),
+            well.radius,
+            Color::srgba(0.6, 0.3, 1.0, 0.15),
+        );
+        gizmos.circle_2d(
+            Isometry2d::from_translation(center),
+            well.core_radius * 1.5,
+            Color::srgba(0.8, 0.4, 1.0, 0.6),
+        );
+    }
+}
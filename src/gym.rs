@@ -0,0 +1,362 @@
+use std::io::{BufRead, Write};
+
+use serde::{Deserialize, Serialize};
+
+use crate::prelude::*;
+
+// 观测中最多包含的小行星数量（按距离从近到远）
+const GYM_MAX_ASTEROIDS: usize = 16;
+// 每损失一条命扣除的奖励
+const GYM_LIFE_PENALTY: f32 = 500.0;
+
+// 训练环境模式（--gym）
+//
+// 无窗口运行，每个固定时间步只在收到指令后推进，速度只受计算能力限制。
+// 标准输入每行一条 JSON 指令，标准输出每行一条 JSON 回复（日志写到标准错误）：
+//
+//   {"cmd": "seed", "seed": 42}
+//       设置之后每次 reset 使用的随机种子，回复 {"ok": true}
+//   {"cmd": "reset"} 或 {"cmd": "reset", "seed": 42}
+//       开始新的一局（可以同时指定种子），回复初始观测
+//   {"cmd": "step", "action": {"forward": true, "rotate_left": false, "rotate_right": false, "fire": true}, "repeat": 4}
+//       按住给定动作推进 repeat 个固定时间步（默认1，每步 1/64 秒），回复观测、这几步的总奖励和是否结束
+//   {"cmd": "close"}
+//       退出程序
+//
+// 观测回复格式：
+//   {"obs": {"ship": {"x", "y", "angle", "vx", "vy", "angvel", "respawning"} 或 null,
+//            "asteroids": [{"dx", "dy", "vx", "vy", "radius", "size", "material"}, ...],
+//            "score", "life", "wave", "arena": [宽, 高]},
+//    "reward": 得分增量 - 500 × 损失的生命, "done": 游戏是否结束, "steps": 本局已进行的步数}
+// 小行星位置是相对飞船的偏移（考虑竞技场边缘相连），角度为弧度，0 表示朝上；出错时回复 {"error": "..."}
+pub struct GymPlugin;
+
+impl Plugin for GymPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<GymEnv>()
+            .init_resource::<GymAction>()
+            // 训练时不进入演示模式
+            .insert_resource(AttractConfig {
+                idle_secs: f32::INFINITY,
+                ..default()
+            })
+            .add_systems(
+                FixedUpdate,
+                apply_gym_action
+                    .before(ship_input_system)
+                    .run_if(in_state(GameState::Running)),
+            )
+            .add_systems(Last, gym_system);
+    }
+}
+
+// 训练程序发来的指令
+#[derive(Debug, Deserialize)]
+#[serde(tag = "cmd", rename_all = "snake_case")]
+enum GymCommand {
+    Seed {
+        seed: u64,
+    },
+    Reset {
+        seed: Option<u64>,
+    },
+    Step {
+        #[serde(default)]
+        action: GymAction,
+        #[serde(default = "default_repeat")]
+        repeat: u32,
+    },
+    Close,
+}
+
+fn default_repeat() -> u32 {
+    1
+}
+
+// 当前按住的动作（资源），对应四个 PlayerAction
+#[derive(Resource, Debug, Default, Clone, Copy, Deserialize)]
+#[serde(default)]
+pub struct GymAction {
+    pub forward: bool,
+    pub rotate_left: bool,
+    pub rotate_right: bool,
+    pub fire: bool,
+}
+
+// 环境当前在做什么
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+enum GymPhase {
+    // 等待下一条指令
+    #[default]
+    Idle,
+    // 正在开始新的一局；left_game 表示已经离开上一局回到主菜单
+    Resetting { left_game: bool },
+    // 还要推进的步数
+    Stepping { remaining: u32 },
+}
+
+// 训练环境状态（资源）
+#[derive(Resource, Debug, Default)]
+struct GymEnv {
+    phase: GymPhase,
+    // seed 指令设置的种子
+    seed: Option<u64>,
+    // 是否已经 reset 过
+    started: bool,
+    // 本局已进行的步数
+    steps: u64,
+    // 上次回复时的分数和生命，用于计算奖励
+    last_score: u32,
+    last_life: u32,
+}
+
+#[derive(Serialize)]
+struct ShipObservation {
+    x: f32,
+    y: f32,
+    angle: f32,
+    vx: f32,
+    vy: f32,
+    angvel: f32,
+    respawning: bool,
+}
+
+#[derive(Serialize)]
+struct AsteroidObservation {
+    dx: f32,
+    dy: f32,
+    vx: f32,
+    vy: f32,
+    radius: f32,
+    size: String,
+    material: String,
+}
+
+#[derive(Serialize)]
+struct Observation {
+    ship: Option<ShipObservation>,
+    asteroids: Vec<AsteroidObservation>,
+    score: u32,
+    life: u32,
+    wave: usize,
+    arena: [f32; 2],
+}
+
+#[derive(Serialize)]
+struct StepReply {
+    obs: Observation,
+    reward: f32,
+    done: bool,
+    steps: u64,
+}
+
+// 把当前动作写入玩家1飞船的 ActionState
+fn apply_gym_action(action: Res<GymAction>, mut ships: Query<(&Ship, &mut ActionState<PlayerAction>)>) {
+    for (ship, mut action_state) in ships.iter_mut() {
+        if ship.player_id != 1 {
+            continue;
+        }
+        for (pressed, player_action) in [
+            (action.forward, PlayerAction::Forward),
+            (action.rotate_left, PlayerAction::RotateLeft),
+            (action.rotate_right, PlayerAction::RotateRight),
+            (action.fire, PlayerAction::Fire),
+        ] {
+            if pressed {
+                action_state.press(&player_action);
+            } else {
+                action_state.release(&player_action);
+            }
+        }
+    }
+}
+
+#[allow(clippy::type_complexity)]
+// 每帧最后推进环境：重置或步进完成时回复观测，空闲时阻塞读取下一条指令
+fn gym_system(
+    mut env: ResMut<GymEnv>,
+    mut action: ResMut<GymAction>,
+    app_state: Res<State<AppState>>,
+    game_state: Option<Res<State<GameState>>>,
+    mut next_app_state: ResMut<NextState<AppState>>,
+    mut game_mode: ResMut<GameMode>,
+    mut game_seed: ResMut<GameSeed>,
+    arena: Option<Res<Arena>>,
+    wave: Option<Res<Wave>>,
+    sprites: Option<Res<SpriteAssets>>,
+    sprite_colliders: Res<SpriteColliders>,
+    ships: Query<(&Ship, &Position, &Rotation, &LinearVelocity, &AngularVelocity, Has<Respawning>)>,
+    asteroids: Query<(&Asteroid, &Position, &LinearVelocity)>,
+    mut app_exit_events: EventWriter<AppExit>,
+) {
+    let running = game_state
+        .as_ref()
+        .is_some_and(|state| *state.get() == GameState::Running);
+    let done = game_state.as_ref().is_some_and(|state| *state.get() == GameState::Over)
+        || (running && !ships.iter().any(|(ship, ..)| ship.player_id == 1 && ship.life > 0));
+
+    match env.phase {
+        GymPhase::Idle => {}
+        GymPhase::Resetting { left_game } => {
+            // 等所有小行星碰撞体生成后再开局，否则同一种子的开局会因加载快慢而不同
+            let assets_ready = sprites.is_some_and(|sprites| sprite_colliders.is_complete(&sprites));
+            match app_state.get() {
+                AppState::Menu if assets_ready => {
+                    *game_mode = GameMode::Classic;
+                    next_app_state.set(AppState::Game);
+                    env.phase = GymPhase::Resetting { left_game: true };
+                }
+                AppState::Game if left_game && running => {
+                    env.phase = GymPhase::Idle;
+                    env.steps = 0;
+                    env.last_score = 0;
+                    env.last_life = START_LIFE;
+                }
+                _ => {}
+            }
+            if env.phase != GymPhase::Idle {
+                return;
+            }
+        }
+        GymPhase::Stepping { remaining } => {
+            env.steps += 1;
+            if remaining > 1 && !done {
+                env.phase = GymPhase::Stepping {
+                    remaining: remaining - 1,
+                };
+                return;
+            }
+            env.phase = GymPhase::Idle;
+        }
+    }
+
+    // 重置或步进刚刚完成，回复观测
+    if env.started && env.phase == GymPhase::Idle && arena.is_some() {
+        let arena = arena.as_deref().unwrap();
+        let observation = observe(arena, wave.as_deref(), &ships, &asteroids);
+        let reward = observation.score.saturating_sub(env.last_score) as f32
+            - env.last_life.saturating_sub(observation.life) as f32 * GYM_LIFE_PENALTY;
+        env.last_score = observation.score;
+        env.last_life = observation.life;
+        reply(&StepReply {
+            obs: observation,
+            reward,
+            done,
+            steps: env.steps,
+        });
+    }
+
+    // 等待下一条会推进游戏的指令
+    loop {
+        let mut line = String::new();
+        match std::io::stdin().lock().read_line(&mut line) {
+            Ok(0) | Err(_) => {
+                // 输入结束，训练程序已退出
+                app_exit_events.write(AppExit::Success);
+                return;
+            }
+            Ok(_) => {}
+        }
+        if line.trim().is_empty() {
+            continue;
+        }
+        match serde_json::from_str::<GymCommand>(&line) {
+            Ok(GymCommand::Seed { seed }) => {
+                env.seed = Some(seed);
+                reply(&serde_json::json!({ "ok": true }));
+            }
+            Ok(GymCommand::Reset { seed }) => {
+                game_seed.0 = seed.or(env.seed);
+                env.started = true;
+                // 开局过程中也会推进时间步，松开上一局按住的动作
+                *action = GymAction::default();
+                env.phase = GymPhase::Resetting {
+                    left_game: *app_state.get() != AppState::Game,
+                };
+                if *app_state.get() == AppState::Game {
+                    next_app_state.set(AppState::Menu);
+                }
+                return;
+            }
+            Ok(GymCommand::Step { .. }) if !env.started => {
+                reply(&serde_json::json!({ "error": "call reset before step" }));
+            }
+            Ok(GymCommand::Step { .. }) if done => {
+                reply(&serde_json::json!({ "error": "episode is done, call reset" }));
+            }
+            Ok(GymCommand::Step {
+                action: step_action,
+                repeat,
+            }) => {
+                *action = step_action;
+                env.phase = GymPhase::Stepping {
+                    remaining: repeat.max(1),
+                };
+                return;
+            }
+            Ok(GymCommand::Close) => {
+                app_exit_events.write(AppExit::Success);
+                return;
+            }
+            Err(error) => {
+                reply(&serde_json::json!({ "error": error.to_string() }));
+            }
+        }
+    }
+}
+
+#[allow(clippy::type_complexity)]
+// 收集玩家1飞船的状态和最近的小行星
+fn observe(
+    arena: &Arena,
+    wave: Option<&Wave>,
+    ships: &Query<(&Ship, &Position, &Rotation, &LinearVelocity, &AngularVelocity, Has<Respawning>)>,
+    asteroids: &Query<(&Asteroid, &Position, &LinearVelocity)>,
+) -> Observation {
+    let player = ships.iter().find(|(ship, ..)| ship.player_id == 1);
+    let center = player.map_or(Vec2::ZERO, |(_, position, ..)| position.0);
+
+    let mut nearby: Vec<AsteroidObservation> = asteroids
+        .iter()
+        .map(|(asteroid, position, linvel)| {
+            let offset = wrapped_offset(position.0 - center, arena.size);
+            AsteroidObservation {
+                dx: offset.x,
+                dy: offset.y,
+                vx: linvel.x,
+                vy: linvel.y,
+                radius: asteroid.size.radius(),
+                size: asteroid.size.to_string(),
+                material: asteroid.material.to_string(),
+            }
+        })
+        .collect();
+    nearby.sort_by(|a, b| (a.dx.hypot(a.dy)).total_cmp(&b.dx.hypot(b.dy)));
+    nearby.truncate(GYM_MAX_ASTEROIDS);
+
+    Observation {
+        ship: player.map(|(_, position, rotation, linvel, angvel, respawning)| ShipObservation {
+            x: position.x,
+            y: position.y,
+            angle: rotation.as_radians(),
+            vx: linvel.x,
+            vy: linvel.y,
+            angvel: angvel.0,
+            respawning,
+        }),
+        asteroids: nearby,
+        score: arena.score,
+        life: player.map_or(0, |(ship, ..)| ship.life),
+        wave: wave.map_or(1, |wave| wave.index + 1),
+        arena: arena.size.to_array(),
+    }
+}
+
+// 向标准输出写一行 JSON 回复
+fn reply(value: &impl Serialize) {
+    let mut stdout = std::io::stdout().lock();
+    if let Ok(line) = serde_json::to_string(value) {
+        let _ = writeln!(stdout, "{line}");
+        let _ = stdout.flush();
+    }
+}
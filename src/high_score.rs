@@ -0,0 +1,150 @@
+use crate::prelude::*;
+
+// 排行榜保留的记录条数
+pub const HIGH_SCORE_COUNT: usize = 5;
+
+// 排行榜中的一条记录
+#[derive(Debug, Clone, Copy)]
+pub struct HighScoreEntry {
+    // 玩家名字缩写（街机风格的三个字母）
+    pub initials: [u8; 3],
+    pub score: u32,
+    // 到达的波次（从1开始）
+    pub wave: usize,
+}
+
+impl HighScoreEntry {
+    pub fn initials(&self) -> &str {
+        std::str::from_utf8(&self.initials).unwrap_or("???")
+    }
+}
+
+// 排行榜（资源），按分数从高到低排列
+#[derive(Debug, Resource)]
+pub struct HighScores {
+    pub entries: Vec<HighScoreEntry>,
+}
+
+impl Default for HighScores {
+    // 街机传统：一开始就有几条默认记录
+    fn default() -> Self {
+        let entry = |initials: &[u8; 3], score, wave| HighScoreEntry {
+            initials: *initials,
+            score,
+            wave,
+        };
+        Self {
+            entries: vec![
+                entry(b"CDL", 20_000, 4),
+                entry(b"NOV", 15_000, 3),
+                entry(b"AST", 10_000, 3),
+                entry(b"RCK", 5_000, 2),
+                entry(b"SNV", 2_500, 1),
+            ],
+        }
+    }
+}
+
+impl HighScores {
+    // 插入一条记录，返回它在排行榜中的名次（从0开始），没有进榜时返回 None
+    pub fn insert(&mut self, entry: HighScoreEntry) -> Option<usize> {
+        let rank = self
+            .entries
+            .iter()
+            .position(|existing| entry.score > existing.score)
+            .unwrap_or(self.entries.len());
+        if rank >= HIGH_SCORE_COUNT {
+            return None;
+        }
+        self.entries.insert(rank, entry);
+        self.entries.truncate(HIGH_SCORE_COUNT);
+        Some(rank)
+    }
+}
+
+// 排行榜插件，负责在每局结束时记录分数
+pub struct HighScorePlugin;
+
+impl Plugin for HighScorePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<HighScores>()
+            .add_systems(OnEnter(GameState::Over), record_high_score);
+    }
+}
+
+// 一局结束时把分数写入排行榜，演示模式中 AI 的分数不记录
+fn record_high_score(
+    arena: Res<Arena>,
+    wave: Option<Res<Wave>>,
+    attract: Res<AttractMode>,
+    mut high_scores: ResMut<HighScores>,
+) {
+    if attract.active {
+        return;
+    }
+    let entry = HighScoreEntry {
+        initials: *b"YOU",
+        score: arena.score,
+        wave: wave.map_or(1, |wave| wave.index + 1),
+    };
+    if let Some(rank) = high_scores.insert(entry) {
+        info!("New high score #{}: {}", rank + 1, arena.score);
+    }
+}
+
+// 生成排行榜表格节点（标题加每条记录一行）
+pub fn spawn_high_score_table(
+    commands: &mut Commands,
+    assets: &UiAssets,
+    high_scores: &HighScores,
+) -> Entity {
+    let title_color = Color::srgb(0.0, 0.7, 0.7);
+    let table = commands
+        .spawn(Node {
+            align_items: AlignItems::Center,
+            flex_direction: FlexDirection::Column,
+            ..default()
+        })
+        .with_child((
+            Text::new("High Scores"),
+            TextFont {
+                font: assets.font.clone(),
+                font_size: 40.0,
+                ..default()
+            },
+            TextColor(title_color),
+            Node {
+                margin: UiRect::bottom(Val::Px(15.)),
+                ..default()
+            },
+        ))
+        .id();
+    for (rank, entry) in high_scores.entries.iter().enumerate() {
+        // 第一名用金色突出
+        let color = if rank == 0 {
+            Color::srgb(1.0, 0.85, 0.2)
+        } else {
+            Color::WHITE
+        };
+        commands.entity(table).with_child((
+            Text::new(format!(
+                "{}.  {}  {:>7}  W{}",
+                rank + 1,
+                entry.initials(),
+                entry.score,
+                entry.wave
+            )),
+            TextFont {
+                font: assets.font_fira.clone(),
+                font_size: 30.0,
+                ..default()
+            },
+            TextColor(color),
+            Node {
+                margin: UiRect::all(Val::Px(4.)),
+                ..default()
+            },
+        ));
+    }
+    table
+}
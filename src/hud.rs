@@ -1,5 +1,3 @@
-use bevy::ecs::spawn::SpawnIter;
-
 use crate::prelude::*;
 
 // 分数UI组件标记
@@ -12,16 +10,95 @@ pub struct UiLife {
     pub min: u32,
 }
 
-// HUD插件，负责分数和生命值显示
+// 生命图标所在的行，图标数量随生命值增减
+#[derive(Component)]
+pub struct UiLifeRow;
+
+// 奖励生命时新图标的放大动画时长（秒）
+const LIFE_PULSE_SECS: f32 = 0.6;
+
+// 新获得生命图标的放大动画
+#[derive(Component)]
+struct UiLifePulse {
+    timer: Timer,
+}
+
+// 得分倍率UI组件标记
+#[derive(Component)]
+pub struct UiMultiplier;
+
+// 得分弹出提示的持续时间（秒）和上升速度（像素/秒）
+const SCORE_POPUP_SECS: f32 = 0.8;
+const SCORE_POPUP_RISE: f32 = 60.0;
+
+// 命中位置上漂浮的“+N”得分提示
+#[derive(Component)]
+struct ScorePopup {
+    timer: Timer,
+}
+
+// 小地图的显示大小（像素）
+const MINIMAP_WIDTH: f32 = 240.0;
+
+// 需要显示在小地图上的实体（飞船、小行星、敌人、道具等），颜色和点的大小（像素）
+#[derive(Component, Debug, Copy, Clone)]
+pub struct MinimapIcon {
+    pub color: Color,
+    pub size: f32,
+}
+
+impl MinimapIcon {
+    pub const SHIP: MinimapIcon = MinimapIcon {
+        color: Color::srgb(0.2, 1.0, 0.4),
+        size: 6.0,
+    };
+    pub const ASTEROID: MinimapIcon = MinimapIcon {
+        color: Color::srgb(0.7, 0.6, 0.5),
+        size: 4.0,
+    };
+    pub const PICKUP: MinimapIcon = MinimapIcon {
+        color: Color::srgb(1.0, 0.9, 0.2),
+        size: 4.0,
+    };
+    pub const GRAVITY_WELL: MinimapIcon = MinimapIcon {
+        color: Color::srgb(0.7, 0.3, 1.0),
+        size: 8.0,
+    };
+}
+
+// 小地图UI容器标记
+#[derive(Component)]
+struct Minimap;
+
+// 小地图上代表某个实体的点
+#[derive(Component)]
+struct MinimapDot {
+    target: Entity,
+}
+
+// 小地图上表示当前摄像机视野的方框
+#[derive(Component)]
+struct MinimapView;
+
+// HUD插件，负责分数、倍率、生命值和小地图显示
 pub struct HudPlugin;
 impl Plugin for HudPlugin {
     fn build(&self, app: &mut App) {
         app.add_systems(
             Update,
-            (hud_score_system, hud_life_system).run_if(in_state(GameState::Running)),
+            (
+                hud_score_system,
+                (hud_life_system, hud_life_pulse_system).chain(),
+                hud_multiplier_system,
+                (spawn_score_popups, score_popup_system).chain(),
+                (minimap_add_dots, minimap_update_dots, minimap_update_view)
+                    .chain()
+                    .run_if(|mode: Res<GameMode>| mode.is_scrolling()),
+            )
+                .run_if(in_state(GameState::Running)),
         )
         // 游戏状态切换到Setup时生成HUD UI
-        .add_systems(OnEnter(GameState::Setup), hud_spawn);
+        .add_systems(OnEnter(GameState::Setup), (hud_spawn, minimap_spawn));
     }
 }
 
@@ -60,9 +137,41 @@ fn hud_spawn(mut commands: Commands, assets: ResMut<UiAssets>) {
             UiScore {}, // 标记为分数UI，方便更新系统识别
         )],
     ));
-    // 生命值图标节点
-    // 注意：此处不在GameOver状态保存生命图标，简化了生命减少的处理
-    let ship_life_image = assets.ship_life.clone(); // 生命图标纹理资源
+    // 得分倍率文本节点，位于分数下方，没有连击时隐藏
+    commands.spawn((
+        Node {
+            position_type: PositionType::Absolute,
+            width: Val::Percent(100.0),
+            height: Val::Percent(100.0),
+            align_items: AlignItems::FlexStart,
+            justify_content: JustifyContent::FlexEnd,
+            flex_direction: FlexDirection::Row,
+            ..default()
+        },
+        StateScoped(AppState::Game),
+        children![(
+            Text::new("x1"),
+            TextFont {
+                font: assets.font.clone(),
+                font_size: 30.0,
+                ..default()
+            },
+            TextColor(Color::srgb_u8(0xFF, 0xCC, 0x00)), // 金色文字
+            TextLayout::new_with_justify(JustifyText::Right),
+            Node {
+                margin: UiRect {
+                    left: Val::Px(10.0),
+                    right: Val::Px(10.0),
+                    top: Val::Px(70.0), // 留出分数文本的高度
+                    bottom: Val::Px(10.0),
+                },
+                ..default()
+            },
+            Visibility::Hidden,
+            UiMultiplier,
+        )],
+    ));
+    // 生命值图标行，图标由 hud_life_system 按当前生命值生成
     commands.spawn((
         Node {
             position_type: PositionType::Absolute,  // 绝对定位
@@ -74,22 +183,7 @@ fn hud_spawn(mut commands: Commands, assets: ResMut<UiAssets>) {
             ..default()
         },
         StateScoped(AppState::Game), // 只在游戏状态显示
-        // 生成多个生命图标实体，范围是 1 到 START_LIFE
-        Children::spawn(SpawnIter((1..(START_LIFE + 1)).map(move |i| {
-            (
-                ImageNode::new(ship_life_image.clone()), // 生命图标图片
-                Node {
-                    margin: UiRect {
-                        left: Val::Px(10.0),
-                        right: Val::Px(10.0),
-                        top: Val::Px(10.0),
-                        bottom: Val::Px(10.0),
-                    },
-                    ..default()
-                },
-                UiLife { min: i }, // 生命图标组件，标记它对应的生命数
-            )
-        })) ),
+        UiLifeRow,
     ));
 }
 
@@ -102,24 +196,249 @@ fn hud_score_system(arena: Res<Arena>, mut query: Query<&mut Text, With<UiScore>
     }
 }
 
-// 生命值更新系统，根据玩家飞船生命动态显示/隐藏生命图标
-fn hud_life_system(
+// 倍率更新系统，连击倍率变化时更新文本，倍率为1时隐藏
+fn hud_multiplier_system(
+    combo: Res<Combo>,
+    mut query: Query<(&mut Text, &mut Visibility), With<UiMultiplier>>,
+) {
+    if combo.is_changed() {
+        for (mut text, mut visibility) in query.iter_mut() {
+            **text = format!("x{}", combo.multiplier);
+            *visibility = if combo.multiplier > 1 {
+                Visibility::Visible
+            } else {
+                Visibility::Hidden
+            };
+        }
+    }
+}
+
+// 根据得分事件在命中位置生成“+N”提示
+fn spawn_score_popups(
+    mut commands: Commands,
+    mut score_events: EventReader<ScoreEvent>,
+    assets: Res<UiAssets>,
+) {
+    for event in score_events.read() {
+        commands.spawn((
+            Name::new("ScorePopup"),
+            Text2d::new(format!("+{}", event.points)),
+            TextFont {
+                font: assets.font.clone(),
+                font_size: 20.0,
+                ..default()
+            },
+            TextColor(Color::srgb_u8(0xFF, 0xCC, 0x00)),
+            Transform::from_translation(Vec3::new(event.x, event.y, 5.0)),
+            ScorePopup {
+                timer: Timer::from_seconds(SCORE_POPUP_SECS, TimerMode::Once),
+            },
+            StateScoped(AppState::Game),
+        ));
+    }
+}
+
+// 得分提示向上漂浮并逐渐淡出，结束后销毁
+fn score_popup_system(
     mut commands: Commands,
-    ship_query: Query<&Ship, Changed<Ship>>, // 监听飞船组件变化
-    mut uilife_query: Query<(Entity, &UiLife)>, // 查询所有生命图标实体和组件
+    time: Res<Time>,
+    mut popups: Query<(Entity, &mut ScorePopup, &mut Transform, &mut TextColor)>,
 ) {
-    let mut life = 0;
-    for ship in ship_query.iter() {
-        if ship.player_id == 1 {
-            life = ship.life; // 获取玩家1当前生命值
+    for (entity, mut popup, mut transform, mut color) in popups.iter_mut() {
+        popup.timer.tick(time.delta());
+        if popup.timer.finished() {
+            commands.entity(entity).despawn();
+            continue;
         }
+        transform.translation.y += SCORE_POPUP_RISE * time.delta_secs();
+        color.0.set_alpha(popup.timer.fraction_remaining());
     }
-    for (entity, uilife) in uilife_query.iter_mut() {
-        // 如果当前生命数 >= 该图标标记的最小生命数，显示图标，否则隐藏
-        commands.entity(entity).insert(if life >= uilife.min {
-            Visibility::Visible
+}
+
+// 生命值更新系统，让生命图标数量与玩家1的生命值一致
+// 因奖励获得的图标会播放放大动画
+fn hud_life_system(
+    mut commands: Commands,
+    assets: Res<UiAssets>,
+    mut extra_life_events: EventReader<ExtraLifeEvent>,
+    ship_query: Query<&Ship>,
+    row_query: Query<Entity, With<UiLifeRow>>,
+    uilife_query: Query<(Entity, &UiLife)>,
+) {
+    let awarded = extra_life_events.read().any(|event| event.player_id == 1);
+    let Ok(row) = row_query.single() else {
+        return;
+    };
+    // 获取玩家1当前生命值，飞船不存在时视为0
+    let life = ship_query
+        .iter()
+        .find(|ship| ship.player_id == 1)
+        .map_or(0, |ship| ship.life);
+
+    let mut shown = 0;
+    for (entity, uilife) in uilife_query.iter() {
+        if uilife.min > life {
+            commands.entity(entity).despawn(); // 多余的图标移除
         } else {
-            Visibility::Hidden
-        });
+            shown += 1;
+        }
+    }
+    for min in (shown + 1)..=life {
+        let mut icon = commands.spawn((
+            ImageNode::new(assets.ship_life.clone()), // 生命图标图片
+            Node {
+                margin: UiRect::all(Val::Px(10.0)),
+                ..default()
+            },
+            UiLife { min }, // 生命图标组件，标记它对应的生命数
+            ChildOf(row),
+        ));
+        if awarded {
+            icon.insert(UiLifePulse {
+                timer: Timer::from_seconds(LIFE_PULSE_SECS, TimerMode::Once),
+            });
+        }
+    }
+}
+
+// 新生命图标先放大再恢复原始大小，同时闪烁金色
+fn hud_life_pulse_system(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut query: Query<(Entity, &mut UiLifePulse, &mut Transform, &mut ImageNode)>,
+) {
+    for (entity, mut pulse, mut transform, mut image) in query.iter_mut() {
+        pulse.timer.tick(time.delta());
+        if pulse.timer.finished() {
+            transform.scale = Vec3::ONE;
+            image.color = Color::WHITE;
+            commands.entity(entity).remove::<UiLifePulse>();
+            continue;
+        }
+        let wave = (pulse.timer.fraction() * std::f32::consts::PI).sin();
+        transform.scale = Vec3::splat(1.0 + wave * 0.8);
+        image.color = Color::WHITE.mix(&Color::srgb_u8(0xFF, 0xCC, 0x00), wave);
+    }
+}
+
+// 大地图模式下在右下角生成小地图
+fn minimap_spawn(mut commands: Commands, mode: Res<GameMode>) {
+    if !mode.is_scrolling() {
+        return;
+    }
+    let arena = mode.arena_size();
+    commands.spawn((
+        Node {
+            position_type: PositionType::Absolute,
+            width: Val::Percent(100.0),
+            height: Val::Percent(100.0),
+            align_items: AlignItems::FlexEnd,       // 垂直方向底部对齐
+            justify_content: JustifyContent::FlexEnd, // 水平方向右对齐
+            ..default()
+        },
+        StateScoped(AppState::Game),
+        children![(
+            Node {
+                width: Val::Px(MINIMAP_WIDTH),
+                height: Val::Px(MINIMAP_WIDTH * arena.y / arena.x), // 与竞技场同比例
+                margin: UiRect::all(Val::Px(10.0)),
+                border: UiRect::all(Val::Px(1.0)),
+                overflow: Overflow::clip(),
+                ..default()
+            },
+            BackgroundColor(Color::srgba(0.0, 0.1, 0.1, 0.5)), // 半透明底色
+            BorderColor(Color::srgb_u8(0x00, 0xAA, 0xAA)),     // 与分数同色的边框
+            Minimap,
+            children![(
+                Node {
+                    position_type: PositionType::Absolute,
+                    border: UiRect::all(Val::Px(1.0)),
+                    ..default()
+                },
+                BorderColor(Color::srgba(1.0, 1.0, 1.0, 0.4)),
+                MinimapView,
+            )],
+        )],
+    ));
+}
+
+// 把竞技场坐标换算成小地图上的百分比位置（左上角为原点）
+fn minimap_position(arena: Vec2, position: Vec2) -> Vec2 {
+    let uv = (position / arena + 0.5).clamp(Vec2::ZERO, Vec2::ONE);
+    Vec2::new(uv.x, 1.0 - uv.y) * 100.0
+}
+
+// 为新出现的小地图实体添加对应的点
+fn minimap_add_dots(
+    mut commands: Commands,
+    minimaps: Query<Entity, With<Minimap>>,
+    icons: Query<(Entity, &MinimapIcon), Added<MinimapIcon>>,
+) {
+    let Ok(minimap) = minimaps.single() else {
+        return;
+    };
+    for (target, icon) in icons.iter() {
+        commands.spawn((
+            Node {
+                position_type: PositionType::Absolute,
+                width: Val::Px(icon.size),
+                height: Val::Px(icon.size),
+                // 让点以实体位置为中心
+                margin: UiRect::new(
+                    Val::Px(-icon.size / 2.0),
+                    Val::ZERO,
+                    Val::Px(-icon.size / 2.0),
+                    Val::ZERO,
+                ),
+                ..default()
+            },
+            BackgroundColor(icon.color),
+            BorderRadius::MAX, // 圆点
+            MinimapDot { target },
+            ChildOf(minimap),
+        ));
+    }
+}
+
+// 跟随实体位置移动小地图上的点，实体消失时删除对应的点
+fn minimap_update_dots(
+    mut commands: Commands,
+    arena: Res<Arena>,
+    targets: Query<&Transform, With<MinimapIcon>>,
+    mut dots: Query<(Entity, &MinimapDot, &mut Node)>,
+) {
+    for (entity, dot, mut node) in dots.iter_mut() {
+        let Ok(transform) = targets.get(dot.target) else {
+            commands.entity(entity).despawn();
+            continue;
+        };
+        let position = minimap_position(arena.size, transform.translation.truncate());
+        node.left = Val::Percent(position.x);
+        node.top = Val::Percent(position.y);
+    }
+}
+
+// 在小地图上画出当前摄像机能看到的范围
+fn minimap_update_view(
+    arena: Res<Arena>,
+    mode: Res<GameMode>,
+    cameras: Query<(&Transform, &Projection), With<MainCamera>>,
+    mut views: Query<&mut Node, With<MinimapView>>,
+) {
+    let Ok((transform, projection)) = cameras.single() else {
+        return;
+    };
+    let zoom = match projection {
+        Projection::Orthographic(ortho) => ortho.scale,
+        _ => 1.0,
+    };
+    let size = mode.view_size() * zoom;
+    let center = transform.translation.truncate();
+    let top_left = minimap_position(arena.size, center + Vec2::new(-size.x, size.y) / 2.0);
+    for mut node in views.iter_mut() {
+        node.left = Val::Percent(top_left.x);
+        node.top = Val::Percent(top_left.y);
+        node.width = Val::Percent(size.x / arena.size.x * 100.0);
+        node.height = Val::Percent(size.y / arena.size.y * 100.0);
     }
 }
@@ -9,6 +9,13 @@ pub struct LaserSpawnEvent {
     pub linvel: LinearVelocity,
 }
 
+// 每发激光造成的伤害
+pub const LASER_DAMAGE: f32 = 1.0;
+// 激光相对发射方向的飞行速度
+pub const LASER_SPEED: f32 = 500.0;
+// 激光存在时间（秒）
+pub const LASER_LIFETIME_SECS: f32 = 2.0;
+
 #[derive(Component)]
 pub struct Laser {
     // 激光存在时间计时器，时间到后销毁激光
@@ -21,13 +28,19 @@ impl Plugin for LaserPlugin {
     fn build(&self, app: &mut App) {
         app.add_event::<LaserSpawnEvent>() // 注册激光生成事件
             .add_systems(
-                Update,
+                FixedUpdate,
                 (
-                    spawn_laser,            // 处理激光生成
                     laser_asteroid_collision, // 处理激光与小行星碰撞
                     laser_timeout_system,   // 激光生命周期计时和销毁
                 )
                 .run_if(in_state(GameState::Running)), // 仅在游戏运行状态处理
+            )
+            // 激光在本步的飞船输入都处理完之后、物理步进之前生成，开火事件不会拖到下一步
+            .add_systems(
+                FixedPostUpdate,
+                spawn_laser
+                    .before(PhysicsSet::Prepare)
+                    .run_if(in_state(GameState::Running)),
             );
     }
 }
@@ -45,13 +58,36 @@ fn spawn_laser(
         transform.translation.z = 2.0;
         // 计算激光速度，考虑发射实体的线速度与激光自身方向速度叠加
         let linvel = LinearVelocity(
-            (spawn_event.linvel.0 * Vec2::Y) + (transform.rotation * Vec3::Y * 500.0).truncate(),
+            (spawn_event.linvel.0 * Vec2::Y) + (transform.rotation * Vec3::Y * LASER_SPEED).truncate(),
         );
-        // 激光碰撞体为矩形，宽2.5，高10.0
-        let collider = Collider::rectangle(2.5, 10.0);
-        // 由于xpbd物理引擎生成的激光没有碰撞质量属性，这里显式添加防止运行时警告
-        let mass_properties = MassPropertiesBundle::from_shape(&collider, 1.0);
+        // 炮口闪光，效果定义在 assets/effects/muzzle_flash.effect
+        commands.trigger(PlayParticleEffect::new(
+            "muzzle_flash",
+            transform.translation.truncate(),
+        ));
+        spawn_laser_entity(&mut commands, &handles, transform, linvel);
+        // 播放激光发射音效；音效用单独的实体播放，播放时加上的 AudioSink 不会改变激光在查询中的顺序
         commands.spawn((
+            AudioPlayer(audios.laser_trigger.clone()),
+            PlaybackSettings::DESPAWN,
+            StateScoped(AppState::Game),
+        ));
+    }
+}
+
+// 生成一发激光实体（不含音效和炮口闪光），回滚和读档时也用它重建激光
+pub fn spawn_laser_entity(
+    commands: &mut Commands,
+    handles: &SpriteAssets,
+    transform: Transform,
+    linvel: LinearVelocity,
+) -> Entity {
+    // 激光碰撞体为矩形，宽2.5，高10.0
+    let collider = Collider::rectangle(2.5, 10.0);
+    // 由于xpbd物理引擎生成的激光没有碰撞质量属性，这里显式添加防止运行时警告
+    let mass_properties = MassPropertiesBundle::from_shape(&collider, 1.0);
+    commands
+        .spawn((
             Name::new("Laser"),   // 实体名称
             Sprite {
                 image: handles.laser.clone(),          // 纹理为激光图像
@@ -60,7 +96,7 @@ fn spawn_laser(
             },
             transform,                // 位置和旋转信息
             Laser {
-                despawn_timer: Timer::from_seconds(2.0, TimerMode::Once), // 激光存在2秒后自动销毁
+                despawn_timer: Timer::from_seconds(LASER_LIFETIME_SECS, TimerMode::Once), // 激光到时后自动销毁
             },
             CollisionLayers::new(GameLayer::Laser, [GameLayer::Asteroid]), // 激光碰撞层，能与小行星碰撞
             CollidingEntities::default(), // 当前碰撞实体列表（初始化为空）
@@ -69,53 +105,72 @@ fn spawn_laser(
             mass_properties,            // 碰撞质量属性
             linvel,                    // 线速度
             Sensor,                    // 传感器，不影响物理碰撞响应
-            AudioPlayer(audios.laser_trigger.clone()), // 播放激光发射音效
             StateScoped(AppState::Game), // 游戏状态作用域
-        ));
-    }
+        ))
+        .id()
 }
 
 // 激光与小行星碰撞处理系统
 fn laser_asteroid_collision(
     mut commands: Commands,
     mut explosion_spawn_events: EventWriter<SpawnExplosionEvent>, // 触发爆炸事件写入器
-    laser_collisions: Query<(Entity, &CollidingEntities), With<Laser>>, // 查询所有激光实体及其碰撞目标
-    is_asteroid: Query<(), With<Asteroid>>, // 查询是否为小行星
-    transforms: Query<&Transform>,          // 查询实体变换组件
+    laser_collisions: Query<(Entity, &CollidingEntities, &Transform), With<Laser>>, // 查询所有激光实体及其碰撞目标
+    asteroids: Query<&Transform, With<Asteroid>>, // 查询小行星位置
 ) {
-    for (laser, targets) in laser_collisions.iter() {
-        for target in targets.iter() {
-            // 如果激光碰撞对象是小行星
-            if is_asteroid.contains(*target) {
-                // 触发小行星受到伤害事件
-                commands.trigger_targets(Damage, *target);
-                // 获取激光位置，用于生成爆炸效果
-                let laser_transform = transforms
-                    .get(laser)
-                    .expect("Missing transform for the laser");
-                // 生成激光击中小行星爆炸特效事件
-                explosion_spawn_events.write(SpawnExplosionEvent {
-                    kind: ExplosionKind::LaserOnAsteroid,
-                    x: laser_transform.translation.x,
-                    y: laser_transform.translation.y,
-                });
-                // 销毁激光实体
-                commands.entity(laser).despawn();
-            }
-        }
+    // 每次命中都会推进连击倍率，同一步里有多发激光命中时处理顺序决定各自的得分；
+    // 查询的遍历顺序取决于实体在表中的位置，按激光位置排序才能让同一局在每台机器上结果一致
+    let mut lasers: Vec<_> = laser_collisions
+        .iter()
+        .map(|(laser, targets, transform)| (laser, targets, transform.translation.truncate()))
+        .collect();
+    lasers.sort_by(|(_, _, a), (_, _, b)| a.x.total_cmp(&b.x).then(a.y.total_cmp(&b.y)));
+    for (laser, targets, laser_position) in lasers {
+        // 一发激光只击中一颗小行星，同时碰到多颗时选最近的
+        // CollidingEntities 是无序集合，不能依赖它的遍历顺序，否则同一种子的结果不可复现
+        let Some(target) = targets
+            .iter()
+            .filter_map(|target| {
+                let transform = asteroids.get(*target).ok()?;
+                Some((*target, transform.translation.truncate().distance(laser_position)))
+            })
+            .min_by(|(_, a), (_, b)| a.total_cmp(b))
+            .map(|(target, _)| target)
+        else {
+            continue;
+        };
+        // 触发小行星受到伤害事件
+        commands.trigger_targets(
+            Damage {
+                amount: LASER_DAMAGE,
+                source: DamageSource::Laser,
+                point: laser_position,
+            },
+            target,
+        );
+        // 生成激光击中小行星爆炸特效事件
+        explosion_spawn_events.write(SpawnExplosionEvent {
+            kind: ExplosionKind::LaserOnAsteroid,
+            x: laser_position.x,
+            y: laser_position.y,
+        });
+        // 销毁激光实体
+        commands.entity(laser).despawn();
     }
 }
 
 // 激光超时销毁系统，基于激光的计时器判断是否销毁
+// 激光超时说明没有击中任何目标，连击中断
 fn laser_timeout_system(
     mut commands: Commands,
     time: Res<Time>,
+    mut combo: ResMut<Combo>,
     mut query: Query<(Entity, &mut Laser)>,
 ) {
     for (entity, mut laser) in query.iter_mut() {
         laser.despawn_timer.tick(time.delta()); // 计时器递增
         if laser.despawn_timer.finished() {
             // 计时结束，销毁激光实体
+            combo.reset();
             commands.entity(entity).despawn();
         }
     }
@@ -4,27 +4,60 @@
 mod arena;
 mod assets;
 mod asteroid;
+mod autopilot;
 mod background;
+mod camera;
+mod combo;
+mod console;
 mod explosion;
+mod gravity_well;
+mod gym;
+mod high_score;
 mod hud;
 mod laser;
+mod match_state;
 mod menu;
+mod netplay;
 mod particle_effects;
+mod perf_overlay;
+mod pickup;
 mod player_ship;
+#[cfg(debug_assertions)]
+mod remote;
+mod save;
+mod spectator;
+mod sprite_collider;
 mod state;
+mod wave;
 
 // 预导入模块，方便在其它模块中直接使用这些常用类型和函数
 mod prelude {
     pub use crate::arena::*;
     pub use crate::assets::*;
     pub use crate::asteroid::*;
+    pub use crate::autopilot::*;
     pub use crate::background::*;
+    pub use crate::camera::*;
+    pub use crate::combo::*;
+    pub use crate::console::*;
     pub use crate::explosion::*;
+    pub use crate::gravity_well::*;
+    pub use crate::gym::*;
+    pub use crate::high_score::*;
     pub use crate::hud::*;
     pub use crate::laser::*;
+    pub use crate::match_state::*;
     pub use crate::menu::*;
+    pub use crate::netplay::*;
+    pub use crate::particle_effects::PlayParticleEffect;
+    pub use crate::perf_overlay::*;
+    pub use crate::pickup::*;
     pub use crate::player_ship::*;
+    pub use crate::save::*;
+    pub use crate::spectator::*;
+    pub use crate::sprite_collider::*;
     pub use crate::state::*;
+    pub use crate::wave::*;
     pub use avian2d::prelude::*;
     pub use bevy::prelude::*;
     pub use bevy::reflect::TypePath;
@@ -33,9 +66,14 @@ mod prelude {
 }
 
 use avian2d::prelude::PhysicsPlugins;
+use std::time::Duration;
+
 use bevy::{
-    remote::{http::RemoteHttpPlugin, RemotePlugin}, // 远程调试插件
-    window::WindowResolution,
+    app::{PluginGroupBuilder, ScheduleRunnerPlugin},
+    render::{settings::WgpuSettings, RenderPlugin},
+    time::TimeUpdateStrategy,
+    window::{ExitCondition, WindowResolution},
+    winit::WinitPlugin,
 };
 
 use crate::prelude::*;
@@ -47,31 +85,171 @@ fn main() {
     // 设置窗口背景色为黑色
     app.insert_resource(ClearColor(Color::srgb_u8(0, 0, 0)));
 
-    // 添加默认插件，并配置主窗口标题和大小
-    app.add_plugins(DefaultPlugins.set(WindowPlugin {
-        primary_window: Some(Window {
-            title: "Supernova".to_string(), // 窗口标题
-            resolution: WindowResolution::new(ARENA_WIDTH, ARENA_HEIGHT), // 窗口尺寸
+    // 命令行参数控制 AI 驾驶、压力测试、训练环境、联机、观战、存档和无窗口运行
+    let cli = Cli::parse(std::env::args().skip(1));
+    let headless = cli.autopilot.headless || cli.gym;
+    let (gym, console) = (cli.gym, cli.console);
+    app.insert_resource(cli.autopilot)
+        .insert_resource(cli.net)
+        .insert_resource(cli.spectator)
+        .insert_resource(cli.save);
+
+    if headless {
+        // 无窗口运行：不创建窗口、不初始化显卡，按 60 帧/秒的节奏运行游戏逻辑；
+        // 训练环境模式不等待，每帧固定前进一个物理时间步，由训练程序的指令驱动
+        app.add_plugins(headless_plugins())
+            .add_plugins(ScheduleRunnerPlugin::run_loop(if gym {
+                Duration::ZERO
+            } else {
+                Duration::from_secs_f64(1.0 / 60.0)
+            }));
+        if gym {
+            app.insert_resource(TimeUpdateStrategy::ManualDuration(Time::<Fixed>::default().timestep()))
+                .add_plugins(GymPlugin);
+        }
+    } else {
+        // 添加默认插件，并配置主窗口标题和大小
+        // 窗口可以自由缩放或全屏，竞技场由摄像机等比缩放适配窗口
+        app.add_plugins(DefaultPlugins.set(WindowPlugin {
+            primary_window: Some(Window {
+                title: "Supernova".to_string(), // 窗口标题
+                resolution: WindowResolution::new(ARENA_WIDTH, ARENA_HEIGHT), // 初始窗口尺寸
+                resizable: true,
+                ..default()
+            }),
             ..default()
-        }),
-        ..default()
-    }));
+        }));
+    }
 
     // 仅在调试模式下启用调试辅助插件
     #[cfg(debug_assertions)]
-    app.add_plugins(PhysicsDebugPlugin::default())  // 物理调试渲染
-        .add_plugins(RemotePlugin::default())       // 远程连接插件（比如VSCode调试器）
-        .add_plugins(RemoteHttpPlugin::default());  // HTTP远程连接支持
+    app.add_plugins(PhysicsDebugPlugin::default())  // 物理调试渲染，F3 开关
+        .add_plugins(remote::RemoteControlPlugin)   // 远程连接插件（比如VSCode调试器）和游戏专用的远程控制方法
+        .add_systems(Update, toggle_physics_debug);
+
+    // 开发者控制台：调试版本中总是可用，发布版本需要 --console 参数
+    if !headless && (cfg!(debug_assertions) || console) {
+        app.add_plugins(ConsolePlugin);
+    }
 
-    // 计算着色器不支持WASM平台，非WASM平台启用粒子特效插件
+    // 计算着色器不支持WASM平台，非WASM平台启用粒子特效插件；无窗口运行时没有渲染，也不需要粒子
     #[cfg(not(target_arch = "wasm32"))]
-    {
+    if !headless {
         app.add_plugins(particle_effects::ParticleEffectsPlugin);
     }
 
+    add_game_plugins(&mut app);
+
+    // 性能调试面板只在有窗口时使用，无窗口运行时不采集诊断数据；物理诊断需要在物理插件之后添加
+    if !headless {
+        app.add_plugins(PerfOverlayPlugin); // F2 开关
+    }
+
+    // 启动游戏
+    app.run();
+}
+
+// 命令行参数，启动时解析一次，再分别交给各个插件的设置资源；未知参数忽略
+#[derive(Debug, Default)]
+struct Cli {
+    // --autopilot、--ai-partner、--soak [局数]、--headless
+    autopilot: AutopilotConfig,
+    // --host [端口]、--join 地址:端口、--net-delay、--net-latency、--net-jitter、--net-loss
+    net: NetConfig,
+    // --broadcast [端口]、--spectate 地址:端口
+    spectator: SpectatorConfig,
+    // --save-file 路径
+    save: SaveConfig,
+    // 以训练环境模式运行（--gym），隐含无窗口运行
+    gym: bool,
+    // 发布版本中启用开发者控制台（--console）
+    console: bool,
+}
+
+impl Cli {
+    fn parse(args: impl IntoIterator<Item = String>) -> Self {
+        let mut cli = Cli::default();
+        let mut args = args.into_iter().peekable();
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--autopilot" => cli.autopilot.player = true,
+                "--ai-partner" => cli.autopilot.partner = true,
+                "--headless" => cli.autopilot.headless = true,
+                "--soak" => {
+                    let games = args.next_if(|next| next.parse::<u32>().is_ok());
+                    cli.autopilot.soak_games = Some(games.map_or(0, |games| games.parse().unwrap()));
+                    cli.autopilot.player = true;
+                }
+                "--gym" => cli.gym = true,
+                "--console" => cli.console = true,
+                "--host" => {
+                    let port = args.next_if(|next| next.parse::<u16>().is_ok());
+                    cli.net.host_port = Some(port.map_or(NET_DEFAULT_PORT, |port| port.parse().unwrap()));
+                }
+                "--join" => cli.net.join_addr = args.next(),
+                "--net-delay" => {
+                    if let Some(delay) = args.next().and_then(|delay| delay.parse().ok()) {
+                        cli.net.delay = delay;
+                    }
+                }
+                "--net-latency" => {
+                    if let Some(latency) = args.next().and_then(|latency| latency.parse().ok()) {
+                        cli.net.conditioner.latency_ms = latency;
+                    }
+                }
+                "--net-jitter" => {
+                    if let Some(jitter) = args.next().and_then(|jitter| jitter.parse().ok()) {
+                        cli.net.conditioner.jitter_ms = jitter;
+                    }
+                }
+                "--net-loss" => {
+                    if let Some(loss) = args.next().and_then(|loss| loss.parse().ok()) {
+                        cli.net.conditioner.loss = loss;
+                    }
+                }
+                "--broadcast" => {
+                    let port = args.next_if(|next| next.parse::<u16>().is_ok());
+                    cli.spectator.broadcast_port =
+                        Some(port.map_or(SPECTATOR_DEFAULT_PORT, |port| port.parse().unwrap()));
+                }
+                "--spectate" => cli.spectator.spectate_addr = args.next(),
+                "--save-file" => {
+                    if let Some(path) = args.next() {
+                        cli.save.path = path.into();
+                    }
+                }
+                _ => {}
+            }
+        }
+        cli
+    }
+}
+
+// 无窗口运行时的默认插件：不创建窗口、不初始化显卡
+fn headless_plugins() -> PluginGroupBuilder {
+    DefaultPlugins
+        .set(WindowPlugin {
+            primary_window: None,
+            exit_condition: ExitCondition::DontExit,
+            ..default()
+        })
+        .set(RenderPlugin {
+            render_creation: WgpuSettings {
+                backends: None,
+                ..default()
+            }
+            .into(),
+            ..default()
+        })
+        .disable::<WinitPlugin>()
+}
+
+// 添加物理系统和游戏各功能插件（有窗口、无窗口运行和测试共用）
+fn add_game_plugins(app: &mut App) {
     // 添加物理系统和菜单输入管理插件
+    // 游戏逻辑和物理都在 FixedUpdate 中按固定时间步运行，渲染时对刚体位置做插值
     app.add_plugins((
-        PhysicsPlugins::default(),
+        PhysicsPlugins::default().set(PhysicsInterpolationPlugin::interpolate_all()),
         InputManagerPlugin::<MenuAction>::default(),
     ));
 
@@ -79,24 +257,87 @@ fn main() {
     app.add_plugins((
         StatesPlugin,       // 游戏状态管理
         AssetsPlugin,       // 资源管理
+        CameraPlugin,       // 摄像机与窗口适配
         ArenaPlugin,        // 竞技场相关逻辑
+        ComboPlugin,        // 连击与得分倍率
         PlayerShipPlugin,   // 玩家飞船
         LaserPlugin,        // 激光系统
         AsteroidPlugin,     // 小行星
+        SpriteColliderPlugin, // 精灵轮廓碰撞体
+        PickupPlugin,       // 道具
         HudPlugin,          // HUD界面
         MenuPlugin,         // 菜单界面
         ExplosionPlugin,    // 爆炸特效
         BackgroundPlugin,   // 背景
     ));
 
-    // 进入Setup状态时执行摄像机初始化
-    app.add_systems(OnEnter(AppState::Setup), setup_camera);
+    // 添加波次与场地危险物插件
+    app.add_plugins((
+        WavePlugin,         // 波次推进与场地布置
+        GravityWellPlugin,  // 引力井与黑洞
+        AutopilotPlugin,    // AI 驾驶与压力测试
+        HighScorePlugin,    // 排行榜
+        AttractPlugin,      // 主菜单闲置时的演示模式
+        NetplayPlugin,      // 双人联机
+        LobbyPlugin,        // 联机界面
+        SpectatorPlugin,    // 观战广播与观看
+        SavePlugin,         // 对局存档与继续
+    ));
+}
 
-    // 启动游戏
-    app.run();
+// 测试用的无窗口应用：每次 update 前进一个固定时间步，返回时资源和碰撞体已经就绪、处于主菜单
+#[cfg(test)]
+fn test_app() -> App {
+    let mut app = App::new();
+    app.add_plugins(headless_plugins().disable::<bevy::log::LogPlugin>())
+        .insert_resource(TimeUpdateStrategy::ManualDuration(Time::<Fixed>::default().timestep()))
+        .insert_resource(AutopilotConfig::default())
+        .insert_resource(NetConfig::default())
+        .insert_resource(SpectatorConfig::default())
+        .insert_resource(SaveConfig::default());
+    add_game_plugins(&mut app);
+    app.finish();
+    app.cleanup();
+    let deadline = std::time::Instant::now() + Duration::from_secs(60);
+    let ready = |world: &World| {
+        world
+            .get_resource::<SpriteAssets>()
+            .is_some_and(|sprites| world.resource::<SpriteColliders>().is_complete(sprites))
+            && *world.resource::<State<AppState>>().get() == AppState::Menu
+    };
+    while !ready(app.world()) {
+        assert!(std::time::Instant::now() < deadline, "assets did not load in time");
+        app.update();
+        std::thread::sleep(Duration::from_millis(1));
+    }
+    app
 }
 
-// 摄像机初始化，生成一个2D摄像机实体
-pub fn setup_camera(mut commands: Commands) {
-    commands.spawn((Name::new("Camera"), Camera2d));
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &str) -> Cli {
+        Cli::parse(args.split_whitespace().map(String::from))
+    }
+
+    #[test]
+    fn one_command_line_fills_every_config() {
+        let cli = parse("--soak 3 --headless --host --net-delay 4 --net-loss 0.1 --spectate 10.0.0.1:9000 --save-file a.json --unknown --console");
+        assert_eq!(cli.autopilot.soak_games, Some(3));
+        assert!(cli.autopilot.player && cli.autopilot.headless && cli.console && !cli.gym);
+        assert_eq!(cli.net.host_port, Some(NET_DEFAULT_PORT));
+        assert_eq!(cli.net.delay, 4);
+        assert_eq!(cli.net.conditioner.loss, 0.1);
+        assert_eq!(cli.spectator.spectate_addr.as_deref(), Some("10.0.0.1:9000"));
+        assert_eq!(cli.spectator.broadcast_port, None);
+        assert_eq!(cli.save.path, std::path::PathBuf::from("a.json"));
+
+        // 省略可选的数值参数时使用默认值，不会吞掉下一个参数
+        let cli = parse("--soak --broadcast --gym");
+        assert_eq!(cli.autopilot.soak_games, Some(0));
+        assert_eq!(cli.spectator.broadcast_port, Some(SPECTATOR_DEFAULT_PORT));
+        assert!(cli.gym);
+        assert_eq!(cli.net.delay, NetConfig::default().delay);
+    }
 }
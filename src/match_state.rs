@@ -0,0 +1,429 @@
+use std::time::Duration;
+
+use bevy::ecs::system::SystemParam;
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::prelude::*;
+
+// 对局状态
+//
+// 记录一局进行中的玩法状态：竞技场（分数、小行星生成计时、已奖励的生命）、波次和波次计时、连击、
+// 随机数生成器的位置，以及飞船、小行星、激光和道具的位置、朝向、速度、生命和计时器。
+// 联机回滚的快照和对局存档都使用它。
+//
+// 实体不直接复制：它们带有观察者、资源句柄和物理引擎的内部组件，恢复时统一通过各自的生成函数重建，
+// 再覆盖记录的状态。爆炸、粒子和得分提示等纯表现效果不记录。
+// 所有计时器都是一次性计时器，记录总时长和已经过的时间；浮点数按原值记录，恢复后完全相同。
+// 引力井由波次决定，不单独记录。
+
+// 一次性计时器的状态
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct SavedTimer {
+    pub duration: Duration,
+    pub elapsed: Duration,
+}
+
+impl From<&Timer> for SavedTimer {
+    fn from(timer: &Timer) -> Self {
+        Self {
+            duration: timer.duration(),
+            elapsed: timer.elapsed(),
+        }
+    }
+}
+
+impl SavedTimer {
+    // 重建计时器；通过 tick 推进已经过的时间，已到时的计时器也会处于完成状态
+    pub fn to_timer(self) -> Timer {
+        let mut timer = Timer::new(self.duration, TimerMode::Once);
+        timer.tick(self.elapsed);
+        timer
+    }
+}
+
+// 刚体的位置、朝向和速度
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct SavedBody {
+    pub position: [f32; 2],
+    /// 朝向的 [cos, sin]，与物理引擎的 Rotation 一致，避免换算成角度带来误差
+    pub rotation: [f32; 2],
+    pub linvel: [f32; 2],
+    pub angvel: f32,
+}
+
+impl SavedBody {
+    fn new(position: &Position, rotation: &Rotation, linvel: &LinearVelocity, angvel: f32) -> Self {
+        Self {
+            position: position.0.into(),
+            rotation: [rotation.cos, rotation.sin],
+            linvel: linvel.0.into(),
+            angvel,
+        }
+    }
+
+    pub fn position(&self) -> Vec2 {
+        Vec2::from(self.position)
+    }
+
+    pub fn rotation(&self) -> Rotation {
+        Rotation {
+            cos: self.rotation[0],
+            sin: self.rotation[1],
+        }
+    }
+
+    // 覆盖生成函数给出的初始位置、朝向和速度
+    pub fn components(&self, z: f32) -> (Transform, Position, Rotation, LinearVelocity, AngularVelocity) {
+        let rotation = self.rotation();
+        (
+            Transform::from_translation(self.position().extend(z)).with_rotation(Quat::from(rotation)),
+            Position(self.position()),
+            rotation,
+            LinearVelocity(Vec2::from(self.linvel)),
+            AngularVelocity(self.angvel),
+        )
+    }
+}
+
+// 随机数生成器的状态：种子、流编号和已经使用的位置
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SavedRng {
+    pub seed: [u8; 32],
+    pub stream: u64,
+    pub word_pos: u128,
+}
+
+impl From<&ChaCha8Rng> for SavedRng {
+    fn from(rng: &ChaCha8Rng) -> Self {
+        Self {
+            seed: rng.get_seed(),
+            stream: rng.get_stream(),
+            word_pos: rng.get_word_pos(),
+        }
+    }
+}
+
+impl SavedRng {
+    // 重建随机数生成器，之后的输出与保存时完全相同
+    pub fn to_rng(&self) -> ChaCha8Rng {
+        let mut rng = ChaCha8Rng::from_seed(self.seed);
+        rng.set_stream(self.stream);
+        rng.set_word_pos(self.word_pos);
+        rng
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SavedCombo {
+    pub hits: u32,
+    pub multiplier: u32,
+    pub window: SavedTimer,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SavedShip {
+    pub player_id: u32,
+    pub life: u32,
+    pub rotation_speed: f32,
+    pub thrust: f32,
+    pub cannon_timer: SavedTimer,
+    pub invincible_timer: SavedTimer,
+    pub invincible_time_secs: f32,
+    /// 等待重生时的重生计时器
+    pub respawning: Option<SavedTimer>,
+    /// 是否是由 AI 驾驶的僚机
+    pub ai: bool,
+    pub body: SavedBody,
+}
+
+impl SavedShip {
+    pub fn to_ship(&self) -> Ship {
+        Ship {
+            rotation_speed: self.rotation_speed,
+            thrust: self.thrust,
+            life: self.life,
+            cannon_timer: self.cannon_timer.to_timer(),
+            player_id: self.player_id,
+            invincible_timer: self.invincible_timer.to_timer(),
+            invincible_time_secs: self.invincible_time_secs,
+        }
+    }
+
+    // 重建飞船（不含操作方式），等待重生的飞船保持隐藏并移出物理模拟
+    pub fn spawn(&self, commands: &mut Commands, handles: &SpriteAssets) -> Entity {
+        let ship = spawn_player_ship(commands, handles, self.player_id, self.body.position());
+        commands
+            .entity(ship)
+            .insert((self.to_ship(), self.body.components(1.0)));
+        if let Some(timer) = self.respawning {
+            commands.entity(ship).insert((
+                Respawning {
+                    timer: timer.to_timer(),
+                },
+                Visibility::Hidden,
+                ColliderDisabled,
+                RigidBodyDisabled,
+            ));
+        }
+        ship
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SavedAsteroid {
+    pub size: AsteroidSize,
+    pub material: AsteroidMaterial,
+    pub health: f32,
+    /// 受击闪白的计时器
+    pub hit_flash: Option<SavedTimer>,
+    pub body: SavedBody,
+}
+
+impl SavedAsteroid {
+    pub fn spawn(&self, commands: &mut Commands, handles: &SpriteAssets, sprite_colliders: &SpriteColliders) -> Entity {
+        let position = self.body.position();
+        let asteroid = spawn_asteroid(
+            commands,
+            handles,
+            sprite_colliders,
+            &AsteroidSpawnEvent {
+                size: self.size,
+                material: self.material,
+                x: position.x,
+                y: position.y,
+                vx: self.body.linvel[0],
+                vy: self.body.linvel[1],
+                angvel: self.body.angvel,
+            },
+        );
+        commands.entity(asteroid).insert((
+            Asteroid {
+                size: self.size,
+                material: self.material,
+                health: self.health,
+            },
+            self.body.components(1.0),
+        ));
+        if let Some(timer) = self.hit_flash {
+            commands.entity(asteroid).insert(HitFlash {
+                timer: timer.to_timer(),
+            });
+        }
+        asteroid
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SavedLaser {
+    pub despawn_timer: SavedTimer,
+    pub body: SavedBody,
+}
+
+impl SavedLaser {
+    pub fn spawn(&self, commands: &mut Commands, handles: &SpriteAssets) -> Entity {
+        let (transform, position, rotation, linvel, _) = self.body.components(2.0);
+        let laser = spawn_laser_entity(commands, handles, transform, linvel);
+        commands.entity(laser).insert((
+            Laser {
+                despawn_timer: self.despawn_timer.to_timer(),
+            },
+            position,
+            rotation,
+        ));
+        laser
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SavedPickup {
+    pub kind: PickupKind,
+    pub despawn_timer: SavedTimer,
+    pub body: SavedBody,
+}
+
+impl SavedPickup {
+    pub fn spawn(&self, commands: &mut Commands, handles: &SpriteAssets) -> Entity {
+        let (_, position, rotation, linvel, _) = self.body.components(1.5);
+        let pickup = spawn_pickup_entity(commands, handles, self.kind, position.0, linvel.0);
+        commands.entity(pickup).insert((
+            Pickup {
+                kind: self.kind,
+                despawn_timer: self.despawn_timer.to_timer(),
+            },
+            position,
+            rotation,
+        ));
+        pickup
+    }
+}
+
+// 一局进行中的对局状态
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MatchState {
+    pub score: u32,
+    pub extra_lives_awarded: u32,
+    pub asteroid_spawn_timer: SavedTimer,
+    /// 当前波次在 WAVES 中的下标
+    pub wave: usize,
+    pub wave_timer: SavedTimer,
+    pub combo: SavedCombo,
+    pub rng: SavedRng,
+    pub ships: Vec<SavedShip>,
+    pub asteroids: Vec<SavedAsteroid>,
+    pub lasers: Vec<SavedLaser>,
+    pub pickups: Vec<SavedPickup>,
+}
+
+impl MatchState {
+    // 恢复竞技场、随机数和连击；波次和实体由调用方处理
+    pub fn restore_resources(&self, arena: &mut Arena, rng: &mut GameRng, combo: &mut Combo) {
+        arena.score = self.score;
+        arena.extra_lives_awarded = self.extra_lives_awarded;
+        arena.asteroid_spawn_timer = self.asteroid_spawn_timer.to_timer();
+        rng.0 = self.rng.to_rng();
+        combo.hits = self.combo.hits;
+        combo.multiplier = self.combo.multiplier;
+        combo.window = self.combo.window.to_timer();
+    }
+}
+
+#[allow(clippy::type_complexity)]
+// 读取进行中对局状态的系统参数
+#[derive(SystemParam)]
+pub struct MatchStateReader<'w, 's> {
+    arena: Res<'w, Arena>,
+    wave: Option<Res<'w, Wave>>,
+    combo: Res<'w, Combo>,
+    rng: Res<'w, GameRng>,
+    ships: Query<
+        'w,
+        's,
+        (
+            &'static Ship,
+            &'static Position,
+            &'static Rotation,
+            &'static LinearVelocity,
+            &'static AngularVelocity,
+            Option<&'static Respawning>,
+            Has<AiPilot>,
+        ),
+    >,
+    asteroids: Query<
+        'w,
+        's,
+        (
+            &'static Asteroid,
+            &'static Position,
+            &'static Rotation,
+            &'static LinearVelocity,
+            &'static AngularVelocity,
+            Option<&'static HitFlash>,
+        ),
+    >,
+    lasers: Query<'w, 's, (&'static Laser, &'static Position, &'static Rotation, &'static LinearVelocity)>,
+    pickups: Query<'w, 's, (&'static Pickup, &'static Position, &'static Rotation, &'static LinearVelocity)>,
+}
+
+impl MatchStateReader<'_, '_> {
+    // 记录当前对局；第一波还没有开始时返回 None
+    pub fn capture(&self) -> Option<MatchState> {
+        let wave = self.wave.as_ref()?;
+        Some(MatchState {
+            score: self.arena.score,
+            extra_lives_awarded: self.arena.extra_lives_awarded,
+            asteroid_spawn_timer: (&self.arena.asteroid_spawn_timer).into(),
+            wave: wave.index,
+            wave_timer: (&wave.timer).into(),
+            combo: SavedCombo {
+                hits: self.combo.hits,
+                multiplier: self.combo.multiplier,
+                window: (&self.combo.window).into(),
+            },
+            rng: (&self.rng.0).into(),
+            ships: self
+                .ships
+                .iter()
+                .map(|(ship, position, rotation, linvel, angvel, respawning, ai)| SavedShip {
+                    player_id: ship.player_id,
+                    life: ship.life,
+                    rotation_speed: ship.rotation_speed,
+                    thrust: ship.thrust,
+                    cannon_timer: (&ship.cannon_timer).into(),
+                    invincible_timer: (&ship.invincible_timer).into(),
+                    invincible_time_secs: ship.invincible_time_secs,
+                    respawning: respawning.map(|respawning| (&respawning.timer).into()),
+                    ai,
+                    body: SavedBody::new(position, rotation, linvel, angvel.0),
+                })
+                .collect(),
+            asteroids: self
+                .asteroids
+                .iter()
+                .map(|(asteroid, position, rotation, linvel, angvel, hit_flash)| SavedAsteroid {
+                    size: asteroid.size,
+                    material: asteroid.material,
+                    health: asteroid.health,
+                    hit_flash: hit_flash.map(|hit_flash| (&hit_flash.timer).into()),
+                    body: SavedBody::new(position, rotation, linvel, angvel.0),
+                })
+                .collect(),
+            lasers: self
+                .lasers
+                .iter()
+                .map(|(laser, position, rotation, linvel)| SavedLaser {
+                    despawn_timer: (&laser.despawn_timer).into(),
+                    body: SavedBody::new(position, rotation, linvel, 0.0),
+                })
+                .collect(),
+            pickups: self
+                .pickups
+                .iter()
+                .map(|(pickup, position, rotation, linvel)| SavedPickup {
+                    kind: pickup.kind,
+                    despawn_timer: (&pickup.despawn_timer).into(),
+                    body: SavedBody::new(position, rotation, linvel, 0.0),
+                })
+                .collect(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::RngCore;
+
+    use super::*;
+
+    fn timer_state(timer: &Timer) -> (Duration, Duration, bool) {
+        (timer.duration(), timer.elapsed(), timer.finished())
+    }
+
+    #[test]
+    fn timer_round_trip_keeps_progress() {
+        let mut running = Timer::from_seconds(2.0, TimerMode::Once);
+        running.tick(Duration::from_millis(750));
+        let mut finished = Timer::from_seconds(0.5, TimerMode::Once);
+        finished.tick(Duration::from_secs(1));
+        assert!(finished.finished());
+        for timer in [running, finished, Timer::from_seconds(1.0, TimerMode::Once)] {
+            let restored = SavedTimer::from(&timer).to_timer();
+            assert_eq!(timer_state(&restored), timer_state(&timer));
+        }
+    }
+
+    #[test]
+    fn rng_round_trip_continues_the_same_sequence() {
+        let mut rng = ChaCha8Rng::seed_from_u64(42);
+        rng.set_stream(3);
+        for _ in 0..37 {
+            rng.next_u32();
+        }
+        let mut restored = SavedRng::from(&rng).to_rng();
+        assert_eq!(restored.get_word_pos(), rng.get_word_pos());
+        let expected: Vec<u64> = (0..16).map(|_| rng.next_u64()).collect();
+        let actual: Vec<u64> = (0..16).map(|_| restored.next_u64()).collect();
+        assert_eq!(actual, expected);
+    }
+}
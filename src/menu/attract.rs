@@ -0,0 +1,223 @@
+use bevy::ecs::system::SystemParam;
+use bevy::time::Stopwatch;
+
+use crate::prelude::*;
+
+// 演示模式设置（资源）
+#[derive(Resource, Debug, Clone)]
+pub struct AttractConfig {
+    /// 主菜单无操作多久后开始演示（秒）
+    pub idle_secs: f32,
+    /// 每段演示最长持续时间（秒），结束后回到主菜单重新计时
+    pub demo_secs: f32,
+    /// 演示遮罩上标题和排行榜轮流显示的间隔（秒）
+    pub panel_secs: f32,
+}
+
+impl Default for AttractConfig {
+    fn default() -> Self {
+        Self {
+            idle_secs: 20.0,
+            demo_secs: 45.0,
+            panel_secs: 6.0,
+        }
+    }
+}
+
+// 演示模式状态（资源）
+// 主菜单闲置一段时间后，由 AI 驾驶在后台进行一局游戏，上面覆盖半透明的菜单遮罩
+#[derive(Resource, Debug, Default)]
+pub struct AttractMode {
+    /// 当前是否正在演示
+    pub active: bool,
+    // 主菜单闲置时间
+    idle: Stopwatch,
+    // 本段演示已进行的时间
+    demo: Stopwatch,
+}
+
+// 演示遮罩上轮流显示的面板，数值为轮换顺序
+#[derive(Component)]
+struct AttractPanel(u32);
+
+// 演示遮罩轮换的面板数量（标题、排行榜）
+const ATTRACT_PANEL_COUNT: u32 = 2;
+
+// 任意按键、鼠标或手柄按钮输入
+#[derive(SystemParam)]
+struct AnyInput<'w, 's> {
+    keys: Res<'w, ButtonInput<KeyCode>>,
+    mouse: Res<'w, ButtonInput<MouseButton>>,
+    gamepads: Query<'w, 's, &'static Gamepad>,
+}
+
+impl AnyInput<'_, '_> {
+    // 本帧是否有任何按下的输入
+    fn just_pressed(&self) -> bool {
+        self.keys.get_just_pressed().next().is_some()
+            || self.mouse.get_just_pressed().next().is_some()
+            || self
+                .gamepads
+                .iter()
+                .any(|gamepad| gamepad.get_just_pressed().next().is_some())
+    }
+}
+
+// 演示模式插件
+pub struct AttractPlugin;
+
+impl Plugin for AttractPlugin {
+    fn build(&self, app: &mut App) {
+        let attract_active = |attract: Res<AttractMode>| attract.active;
+        app.init_resource::<AttractConfig>()
+            .init_resource::<AttractMode>()
+            // 回到主菜单时结束演示并重新开始计时
+            .add_systems(OnEnter(AppState::Menu), reset_attract)
+            .add_systems(Update, attract_idle_system.run_if(in_state(AppState::Menu)))
+            .add_systems(OnEnter(AppState::Game), spawn_attract_overlay.run_if(attract_active))
+            .add_systems(
+                Update,
+                (attract_demo_system, attract_panel_system)
+                    .run_if(in_state(AppState::Game).and(attract_active)),
+            )
+            .add_systems(
+                FixedUpdate,
+                attach_demo_pilot.run_if(in_state(GameState::Running).and(attract_active)),
+            );
+    }
+}
+
+fn reset_attract(mut attract: ResMut<AttractMode>) {
+    *attract = AttractMode::default();
+}
+
+// 主菜单闲置计时，有任何输入就重新计时，闲置足够久后以经典模式开始演示
+fn attract_idle_system(
+    time: Res<Time<Real>>,
+    input: AnyInput,
+    config: Res<AttractConfig>,
+    mut attract: ResMut<AttractMode>,
+    mut game_mode: ResMut<GameMode>,
+    mut next_app_state: ResMut<NextState<AppState>>,
+) {
+    if input.just_pressed() {
+        attract.idle.reset();
+        return;
+    }
+    attract.idle.tick(time.delta());
+    if attract.idle.elapsed_secs() >= config.idle_secs {
+        attract.active = true;
+        *game_mode = GameMode::Classic;
+        next_app_state.set(AppState::Game);
+    }
+}
+
+// 演示中任何输入、演示时间到或 AI 游戏结束时回到主菜单
+fn attract_demo_system(
+    time: Res<Time<Real>>,
+    input: AnyInput,
+    config: Res<AttractConfig>,
+    game_state: Option<Res<State<GameState>>>,
+    mut attract: ResMut<AttractMode>,
+    mut next_app_state: ResMut<NextState<AppState>>,
+) {
+    attract.demo.tick(time.delta());
+    let game_over = game_state.is_some_and(|state| *state.get() == GameState::Over);
+    if input.just_pressed() || game_over || attract.demo.elapsed_secs() >= config.demo_secs {
+        next_app_state.set(AppState::Menu);
+    }
+}
+
+// 演示中玩家飞船由 AI 驾驶
+fn attach_demo_pilot(mut commands: Commands, ships: Query<Entity, Added<Ship>>) {
+    for entity in ships.iter() {
+        commands.entity(entity).insert(AiPilot::default());
+    }
+}
+
+// 生成半透明的演示遮罩：标题、轮流显示的说明和排行榜，以及闪烁的按键提示
+fn spawn_attract_overlay(
+    mut commands: Commands,
+    assets: Res<UiAssets>,
+    high_scores: Res<HighScores>,
+) {
+    let title_color = Color::srgb(0.0, 0.7, 0.7);
+    let table = spawn_high_score_table(&mut commands, &assets, &high_scores);
+    commands.entity(table).insert((
+        AttractPanel(1),
+        Node {
+            align_items: AlignItems::Center,
+            flex_direction: FlexDirection::Column,
+            display: Display::None,
+            ..default()
+        },
+    ));
+    commands
+        .spawn((
+            Node {
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                align_items: AlignItems::Center,
+                justify_content: JustifyContent::SpaceEvenly,
+                flex_direction: FlexDirection::Column,
+                ..default()
+            },
+            BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.5)),
+            GlobalZIndex(10), // 盖在 HUD 之上
+            StateScoped(AppState::Game),
+            children![
+                (
+                    Text::new("Supernova"),
+                    TextFont {
+                        font: assets.font.clone(),
+                        font_size: 120.0,
+                        ..default()
+                    },
+                    TextColor(title_color),
+                ),
+                (
+                    AttractPanel(0),
+                    Text::new("Demo"),
+                    TextFont {
+                        font: assets.font.clone(),
+                        font_size: 40.0,
+                        ..default()
+                    },
+                    TextColor(Color::WHITE),
+                ),
+            ],
+        ))
+        .add_child(table)
+        .with_child((
+            Text::new("Press any key"),
+            TextFont {
+                font: assets.font.clone(),
+                font_size: 30.0,
+                ..default()
+            },
+            TextColor(title_color),
+            DrawBlink {
+                timer: Timer::from_seconds(0.5, TimerMode::Repeating),
+                enabled: true,
+            },
+        ));
+}
+
+// 按时间轮流显示遮罩上的面板
+fn attract_panel_system(
+    attract: Res<AttractMode>,
+    config: Res<AttractConfig>,
+    mut panels: Query<(&AttractPanel, &mut Node)>,
+) {
+    let current = (attract.demo.elapsed_secs() / config.panel_secs) as u32 % ATTRACT_PANEL_COUNT;
+    for (panel, mut node) in panels.iter_mut() {
+        let display = if panel.0 == current {
+            Display::Flex
+        } else {
+            Display::None
+        };
+        if node.display != display {
+            node.display = display;
+        }
+    }
+}
@@ -3,8 +3,11 @@ use crate::prelude::*;
 /// 主菜单输入系统
 /// 处理主菜单和制作人员名单界面的按钮选择逻辑
 pub fn main_menu_input_system(
+    mut commands: Commands,
+    save_config: Res<SaveConfig>,                           // 存档位置
     app_state: ResMut<State<AppState>>,                      // 当前 App 状态
     mut next_app_state: ResMut<NextState<AppState>>,        // 下一个 App 状态
+    mut game_mode: ResMut<GameMode>,                        // 选择的游戏模式
     menu_action_state: Res<ActionState<MenuAction>>,        // 菜单动作状态（是否按下了按钮）
     mut app_exit_events: EventWriter<AppExit>,              // 写入退出事件
     menu: Query<&MenuHandler>,                              // 查询菜单选项状态（选中的 ID）
@@ -15,13 +18,40 @@ pub fn main_menu_input_system(
         if menu_action_state.just_pressed(&MenuAction::Accept) {
             // 当前处于主菜单状态
             if app_state.get() == &AppState::Menu {
-                match menu.selected_id {
-                    0 => {
-                        // 选项 0：进入游戏
+                // 是否有 Continue 取决于存档，按选项文字区分
+                match menu.entries.get(menu.selected_id as usize).map(String::as_str) {
+                    Some("Continue") => {
+                        // 继续上次保存的对局，存档在进入菜单后可能已被删除或损坏
+                        match SaveGame::load(&save_config.path) {
+                            Ok(save) => {
+                                *game_mode = save.mode.into();
+                                commands.insert_resource(RestoreGame(save));
+                                next_app_state.set(AppState::Game);
+                            }
+                            Err(error) => warn!("Cannot continue: {error}"),
+                        }
+                    }
+                    Some("Play") => {
+                        // 以经典模式进入游戏
+                        *game_mode = GameMode::Classic;
+                        next_app_state.set(AppState::Game);
+                    }
+                    Some("Wide") => {
+                        // 以宽屏模式进入游戏
+                        *game_mode = GameMode::Widescreen;
+                        next_app_state.set(AppState::Game);
+                    }
+                    Some("Big") => {
+                        // 以大地图模式进入游戏
+                        *game_mode = GameMode::BigArena;
                         next_app_state.set(AppState::Game);
                     }
-                    1 => {
-                        // 选项 1：查看制作人员名单
+                    Some("Online") => {
+                        // 进入联机界面
+                        next_app_state.set(AppState::Lobby);
+                    }
+                    Some("Credits") => {
+                        // 查看制作人员名单
                         next_app_state.set(AppState::Credits);
                     }
                     _ => {
@@ -55,10 +85,19 @@ pub fn game_menu_input_system(
     mut next_game_state: ResMut<NextState<GameState>>,      // 下一个游戏状态
     menu_action_state: Res<ActionState<MenuAction>>,        // 菜单动作状态
     mut app_exit_events: EventWriter<AppExit>,              // 写入退出事件
+    mut save_events: EventWriter<SaveAndQuitEvent>,         // 写入存档事件
+    mut net_session: Option<ResMut<NetSession>>,            // 联机会话（联机时存在）
     menu: Query<&MenuHandler>,                              // 查询菜单选项状态
 ) {
-    // 按下暂停/恢复按钮
-    if menu_action_state.just_pressed(&MenuAction::PauseUnpause) {
+    // 联机时暂停和继续要通知对方，由联机插件统一切换双方的游戏状态
+    if let Some(session) = net_session.as_mut() {
+        if menu_action_state.just_pressed(&MenuAction::PauseUnpause)
+            && matches!(game_state.get(), GameState::Running | GameState::Paused)
+        {
+            let paused = session.is_paused();
+            session.set_paused(!paused);
+        }
+    } else if menu_action_state.just_pressed(&MenuAction::PauseUnpause) {
         if game_state.get() == &GameState::Running {
             // 当前为运行状态，转为暂停
             next_game_state.set(GameState::Paused);
@@ -75,13 +114,21 @@ pub fn game_menu_input_system(
         if menu_action_state.just_pressed(&MenuAction::Accept) {
             // 如果当前是暂停状态的菜单
             if game_state.get() == &GameState::Paused {
-                match menu.selected_id {
-                    0 => {
-                        // 选项 0：继续游戏
-                        next_game_state.set(GameState::Running);
+                // 联机时没有 Save & Quit，按选项文字区分
+                match menu.entries.get(menu.selected_id as usize).map(String::as_str) {
+                    Some("Resume") => {
+                        // 继续游戏
+                        match net_session.as_mut() {
+                            Some(session) => session.set_paused(false),
+                            None => next_game_state.set(GameState::Running),
+                        }
+                    }
+                    Some("Save & Quit") => {
+                        // 保存对局，保存成功后回到主菜单
+                        save_events.write(SaveAndQuitEvent);
                     }
-                    1 => {
-                        // 选项 1：返回主菜单
+                    Some("Menu") => {
+                        // 返回主菜单
                         next_app_state.set(AppState::Menu);
                     }
                     _ => {
@@ -1,12 +1,16 @@
 use crate::prelude::*;
 
 // 引入菜单相关模块
+mod attract;
 mod handler;
 mod interaction;
+mod online;
 
-// 暴露 handler 和 interaction 供外部使用
+// 暴露 attract、handler、interaction 和 online 供外部使用
+pub use attract::*;
 pub use handler::*;
 pub use interaction::*;
+pub use online::*;
 
 // 定义菜单交互相关的用户动作
 #[derive(Actionlike, PartialEq, Eq, Clone, Copy, Hash, Debug, Reflect)]
@@ -48,10 +52,12 @@ impl Plugin for MenuPlugin {
                 ),
             )
             
-            // 游戏状态下运行游戏菜单输入系统（仅限 AppState::Game）
+            // 游戏状态下运行游戏菜单输入系统（仅限 AppState::Game，演示中任何按键都直接回到主菜单）
             .add_systems(
                 Update,
-                game_menu_input_system.run_if(in_state(AppState::Game)),
+                game_menu_input_system.run_if(
+                    in_state(AppState::Game).and(|attract: Res<AttractMode>| !attract.active),
+                ),
             );
     }
 }
@@ -74,13 +80,25 @@ fn setup(mut commands: Commands) {
 }
 
 // 生成主菜单界面
-fn spawn_main_menu(mut commands: Commands, assets: ResMut<UiAssets>) {
+fn spawn_main_menu(mut commands: Commands, assets: ResMut<UiAssets>, save_config: Res<SaveConfig>) {
+    let mut entries: Vec<String> = vec![
+        "Play".into(),
+        "Wide".into(), // 宽屏模式
+        "Big".into(),  // 大地图模式
+        "Online".into(), // 双人联机
+        "Credits".into(),
+        "Exit".into(),
+    ];
+    // 有可以继续的存档时，把 Continue 放在第一项
+    if SaveGame::load(&save_config.path).is_ok() {
+        entries.insert(0, "Continue".into());
+    }
     let entity = MenuHandler {
         main_text: "Supernova".into(),                       // 菜单标题文字
         main_text_color: Color::srgb(0.0, 0.7, 0.7),        // 标题颜色
         main_text_blink: false,                             // 是否闪烁
         selected_id: 0,                                     // 默认选中第一个选项
-        entries: vec!["Play".into(), "Credits".into(), "Exit".into()],
+        entries,
     }
     .spawn(&mut commands, assets.font.clone());
 
@@ -103,13 +121,19 @@ fn spawn_gameover_menu(mut commands: Commands, assets: ResMut<UiAssets>) {
 }
 
 // 生成暂停菜单
-fn spawn_pause_menu(mut commands: Commands, assets: ResMut<UiAssets>) {
+fn spawn_pause_menu(mut commands: Commands, assets: ResMut<UiAssets>, session: Option<Res<NetSession>>) {
+    // 联机对局不能存档
+    let entries = if session.is_some() {
+        vec!["Resume".into(), "Menu".into(), "Exit".into()]
+    } else {
+        vec!["Resume".into(), "Save & Quit".into(), "Menu".into(), "Exit".into()]
+    };
     let entity = MenuHandler {
         main_text: "Pause".into(),
         main_text_color: Color::srgb_u8(0xF8, 0xE4, 0x73),
         main_text_blink: true,
         selected_id: 0,
-        entries: vec!["Resume".into(), "Menu".into(), "Exit".into()],
+        entries,
     }
     .spawn(&mut commands, assets.font.clone());
 
@@ -0,0 +1,306 @@
+use std::net::{SocketAddr, ToSocketAddrs};
+
+use bevy::input::{
+    keyboard::{Key, KeyboardInput},
+    ButtonState,
+};
+
+use crate::prelude::*;
+
+// 联机界面（资源）：要加入的地址和最近一次的错误信息
+#[derive(Resource, Debug)]
+pub struct Lobby {
+    /// 加入时连接的地址，可以直接输入数字、点和冒号修改
+    pub address: String,
+    // 建立主机或加入失败的原因
+    error: Option<String>,
+}
+
+impl Default for Lobby {
+    fn default() -> Self {
+        Self {
+            address: format!("127.0.0.1:{NET_DEFAULT_PORT}"),
+            error: None,
+        }
+    }
+}
+
+// 联机界面上的地址文字
+#[derive(Component)]
+struct LobbyAddressText;
+
+// 联机界面上的连接状态文字
+#[derive(Component)]
+struct LobbyStatusText;
+
+// 联机界面插件：主菜单 Online 进入，可以建立主机，或输入地址加入、观战
+pub struct LobbyPlugin;
+
+impl Plugin for LobbyPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Lobby>()
+            .add_systems(OnEnter(AppState::Lobby), spawn_lobby_menu)
+            .add_systems(OnEnter(AppState::Menu), leave_lobby)
+            .add_systems(Update, lobby_auto_enter.run_if(in_state(AppState::Menu)))
+            .add_systems(
+                Update,
+                (
+                    lobby_auto_start,
+                    lobby_address_input_system,
+                    lobby_menu_input_system,
+                    lobby_text_system,
+                )
+                    .chain()
+                    .run_if(in_state(AppState::Lobby)),
+            );
+    }
+}
+
+// 生成联机界面：菜单、地址和连接状态
+fn spawn_lobby_menu(mut commands: Commands, assets: Res<UiAssets>, lobby: Res<Lobby>) {
+    let entity = MenuHandler {
+        main_text: "Online".into(),
+        main_text_color: Color::srgb(0.0, 0.7, 0.7),
+        main_text_blink: false,
+        selected_id: 0,
+        entries: vec!["Host".into(), "Join".into(), "Watch".into(), "Back".into()],
+    }
+    .spawn(&mut commands, assets.font.clone());
+    commands.entity(entity).insert(StateScoped(AppState::Lobby));
+
+    let text_font = TextFont {
+        font: assets.font_fira.clone(),
+        font_size: 22.0,
+        ..default()
+    };
+    commands.spawn((
+        Node {
+            position_type: PositionType::Absolute,
+            width: Val::Percent(100.0),
+            bottom: Val::Px(40.0),
+            align_items: AlignItems::Center,
+            flex_direction: FlexDirection::Column,
+            ..default()
+        },
+        StateScoped(AppState::Lobby),
+        children![
+            (
+                LobbyAddressText,
+                Text::new(format!("Address: {}_", lobby.address)),
+                text_font.clone(),
+                TextColor(Color::WHITE),
+            ),
+            (
+                LobbyStatusText,
+                Text::default(),
+                text_font,
+                TextColor(Color::srgb(0.5, 0.5, 0.5)),
+                Node {
+                    margin: UiRect::top(Val::Px(8.0)),
+                    ..default()
+                },
+            ),
+        ],
+    ));
+}
+
+// 回到主菜单时离开尚未开始的联机
+fn leave_lobby(mut commands: Commands, session: Option<ResMut<NetSession>>, mut lobby: ResMut<Lobby>) {
+    if let Some(mut session) = session {
+        session.leave();
+        commands.remove_resource::<NetSession>();
+    }
+    lobby.error = None;
+}
+
+// 命令行指定了 --host 或 --join 时，从主菜单直接进入联机界面
+fn lobby_auto_enter(config: Res<NetConfig>, mut next_app_state: ResMut<NextState<AppState>>) {
+    if config.auto_start() {
+        next_app_state.set(AppState::Lobby);
+    }
+}
+
+// 命令行指定了 --host 或 --join 时，进入联机界面后自动建立主机或加入；连接失败时退出程序
+fn lobby_auto_start(
+    mut commands: Commands,
+    config: Res<NetConfig>,
+    mut lobby: ResMut<Lobby>,
+    session: Option<Res<NetSession>>,
+    mut started: Local<bool>,
+    mut app_exit_events: EventWriter<AppExit>,
+) {
+    if !config.auto_start() {
+        return;
+    }
+    if !*started {
+        *started = true;
+        if let Some(address) = &config.join_addr {
+            lobby.address.clone_from(address);
+        }
+        let result = match config.host_port {
+            Some(port) => start_host(port, &config),
+            None => start_join(&lobby.address, &config),
+        };
+        match result {
+            Ok(session) => commands.insert_resource(session),
+            Err(error) => {
+                error!("Netplay failed: {error}");
+                app_exit_events.write(AppExit::error());
+            }
+        }
+        return;
+    }
+    if let Some(session) = session {
+        if let NetStatus::Disconnected(reason) = &session.status {
+            error!("Netplay failed: {reason}");
+            app_exit_events.write(AppExit::error());
+        }
+    }
+}
+
+fn start_host(port: u16, config: &NetConfig) -> Result<NetSession, String> {
+    let session = NetSession::host(port, config).map_err(|error| format!("cannot host on port {port}: {error}"))?;
+    info!("Hosting on port {port}");
+    Ok(session)
+}
+
+fn start_join(address: &str, config: &NetConfig) -> Result<NetSession, String> {
+    // 没有写端口时使用默认端口
+    let address = if address.contains(':') {
+        address.to_string()
+    } else {
+        format!("{address}:{NET_DEFAULT_PORT}")
+    };
+    let addr: SocketAddr = address
+        .to_socket_addrs()
+        .ok()
+        .and_then(|mut addrs| addrs.next())
+        .ok_or_else(|| format!("invalid address `{address}`"))?;
+    let session = NetSession::join(addr, config).map_err(|error| format!("cannot join {addr}: {error}"))?;
+    info!("Joining {addr}");
+    Ok(session)
+}
+
+// 输入要加入的地址：数字、点、冒号和退格
+fn lobby_address_input_system(
+    mut events: EventReader<KeyboardInput>,
+    mut lobby: ResMut<Lobby>,
+    session: Option<Res<NetSession>>,
+) {
+    // 已经在建立主机或连接时不再修改地址
+    if session.is_some() {
+        events.clear();
+        return;
+    }
+    for event in events.read() {
+        if event.state != ButtonState::Pressed {
+            continue;
+        }
+        match &event.logical_key {
+            Key::Backspace => {
+                lobby.address.pop();
+            }
+            Key::Character(text) => {
+                let text: String = text
+                    .chars()
+                    .filter(|c| c.is_ascii_digit() || *c == '.' || *c == ':')
+                    .collect();
+                if !text.is_empty() && lobby.address.len() < 40 {
+                    lobby.address.push_str(&text);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+// 联机界面的菜单：建立主机、加入、观战、返回主菜单
+fn lobby_menu_input_system(
+    mut commands: Commands,
+    config: Res<NetConfig>,
+    mut lobby: ResMut<Lobby>,
+    mut session: Option<ResMut<NetSession>>,
+    menu_action_state: Res<ActionState<MenuAction>>,
+    mut next_app_state: ResMut<NextState<AppState>>,
+    menu: Query<&MenuHandler>,
+) {
+    let Ok(menu) = menu.single() else {
+        return;
+    };
+    if menu_action_state.just_pressed(&MenuAction::PauseUnpause) {
+        next_app_state.set(AppState::Menu);
+        return;
+    }
+    if !menu_action_state.just_pressed(&MenuAction::Accept) {
+        return;
+    }
+    // 已经建立主机或正在加入时，再次选择主机或加入会取消
+    if menu.selected_id <= 1 {
+        if let Some(session) = session.as_mut() {
+            session.leave();
+            commands.remove_resource::<NetSession>();
+            return;
+        }
+    }
+    let result = match menu.selected_id {
+        0 => start_host(NET_DEFAULT_PORT, &config),
+        1 => start_join(&lobby.address, &config),
+        // 观看该地址广播的画面
+        2 => {
+            if let Some(session) = session.as_mut() {
+                session.leave();
+                commands.remove_resource::<NetSession>();
+            }
+            match SpectatorClient::connect(&lobby.address) {
+                Ok(client) => {
+                    commands.insert_resource(client);
+                    next_app_state.set(AppState::Spectate);
+                }
+                Err(error) => {
+                    warn!("{error}");
+                    lobby.error = Some(error);
+                }
+            }
+            return;
+        }
+        _ => {
+            next_app_state.set(AppState::Menu);
+            return;
+        }
+    };
+    match result {
+        Ok(session) => {
+            lobby.error = None;
+            commands.insert_resource(session);
+        }
+        Err(error) => {
+            warn!("{error}");
+            lobby.error = Some(error);
+        }
+    }
+}
+
+// 更新地址和连接状态文字
+fn lobby_text_system(
+    lobby: Res<Lobby>,
+    session: Option<Res<NetSession>>,
+    mut address_texts: Query<&mut Text, (With<LobbyAddressText>, Without<LobbyStatusText>)>,
+    mut status_texts: Query<&mut Text, (With<LobbyStatusText>, Without<LobbyAddressText>)>,
+) {
+    let address = format!("Address: {}_", lobby.address);
+    for mut text in address_texts.iter_mut() {
+        if text.0 != address {
+            text.0 = address.clone();
+        }
+    }
+    let status = match (&lobby.error, &session) {
+        (Some(error), _) => error.clone(),
+        (None, Some(session)) => session.status_text(),
+        (None, None) => "Host a game, or type the host address and join or watch".to_string(),
+    };
+    for mut text in status_texts.iter_mut() {
+        if text.0 != status {
+            text.0 = status.clone();
+        }
+    }
+}
@@ -0,0 +1,218 @@
+use std::io::ErrorKind;
+use std::net::{SocketAddr, UdpSocket};
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+
+use crate::prelude::*;
+
+// 联机协议版本，双方不一致时拒绝连接
+pub const NET_PROTOCOL_VERSION: u32 = 1;
+// 单个数据包的最大长度（字节）
+const NET_MAX_PACKET: usize = 1400;
+
+// 联机数据包，每个 UDP 包一条，用 JSON 编码
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum NetMessage {
+    // 加入方请求连接，重复发送直到收到 Welcome
+    Hello { version: u32 },
+    // 主机接受连接，告知本局的随机种子和输入延迟
+    Welcome { version: u32, seed: u64, delay: u32 },
+    // 主机拒绝连接（版本不一致或已有玩家）
+    Reject { reason: String },
+    // 从 start 帧开始连续若干帧的本地输入（未确认的输入每次都重发，以应对丢包）
+    // ack 表示已经连续收到对方 ack 之前所有帧的输入，checksums 为最近几次的状态校验值（帧, 校验值），
+    // pauses 为发送方所知的双方累计暂停/继续次数
+    Inputs {
+        start: u32,
+        inputs: Vec<u8>,
+        ack: u32,
+        checksums: Vec<(u32, u64)>,
+        pauses: u32,
+    },
+    // 测量往返延迟
+    Ping { time_ms: u64 },
+    Pong { time_ms: u64 },
+    // 离开本局
+    Bye,
+}
+
+// 模拟网络延迟和丢包的设置，用于在本机测试联机
+#[derive(Debug, Default, Clone, Copy)]
+pub struct LinkConditioner {
+    /// 单程固定延迟（毫秒）
+    pub latency_ms: u64,
+    /// 单程随机抖动上限（毫秒）
+    pub jitter_ms: u64,
+    /// 丢包率（0~1）
+    pub loss: f32,
+}
+
+impl LinkConditioner {
+    pub fn is_active(&self) -> bool {
+        self.latency_ms > 0 || self.jitter_ms > 0 || self.loss > 0.0
+    }
+}
+
+// 非阻塞的 UDP 连接，发送时可以经过模拟的延迟和丢包
+pub struct NetSocket {
+    socket: UdpSocket,
+    conditioner: LinkConditioner,
+    // 等待模拟延迟结束后再发出的数据包
+    delayed: Vec<(Instant, SocketAddr, Vec<u8>)>,
+}
+
+impl NetSocket {
+    pub fn bind(addr: SocketAddr, conditioner: LinkConditioner) -> std::io::Result<Self> {
+        let socket = UdpSocket::bind(addr)?;
+        socket.set_nonblocking(true)?;
+        Ok(Self {
+            socket,
+            conditioner,
+            delayed: Vec::new(),
+        })
+    }
+
+    pub fn local_addr(&self) -> Option<SocketAddr> {
+        self.socket.local_addr().ok()
+    }
+
+    // 发送一条消息；开启模拟时按丢包率丢弃，或延迟到 flush 时再发出
+    pub fn send(&mut self, addr: SocketAddr, message: &NetMessage) {
+        let Ok(bytes) = serde_json::to_vec(message) else {
+            return;
+        };
+        if !self.conditioner.is_active() {
+            let _ = self.socket.send_to(&bytes, addr);
+            return;
+        }
+        let mut rng = thread_rng();
+        if rng.gen::<f32>() < self.conditioner.loss {
+            return;
+        }
+        let jitter = if self.conditioner.jitter_ms > 0 {
+            rng.gen_range(0..=self.conditioner.jitter_ms)
+        } else {
+            0
+        };
+        let delay = Duration::from_millis(self.conditioner.latency_ms + jitter);
+        self.delayed.push((Instant::now() + delay, addr, bytes));
+    }
+
+    // 发出模拟延迟已经结束的数据包
+    pub fn flush(&mut self) {
+        let now = Instant::now();
+        let socket = &self.socket;
+        self.delayed.retain(|(due, addr, bytes)| {
+            if *due > now {
+                return true;
+            }
+            let _ = socket.send_to(bytes, addr);
+            false
+        });
+    }
+
+    // 取出所有已收到的消息，无法解析的数据包直接丢弃
+    pub fn receive(&mut self) -> Vec<(SocketAddr, NetMessage)> {
+        let mut messages = Vec::new();
+        let mut buffer = [0u8; NET_MAX_PACKET];
+        loop {
+            match self.socket.recv_from(&mut buffer) {
+                Ok((len, addr)) => {
+                    if let Ok(message) = serde_json::from_slice(&buffer[..len]) {
+                        messages.push((addr, message));
+                    }
+                }
+                // 对方端口尚未打开或已经关闭时会收到这类错误，忽略即可
+                Err(error)
+                    if matches!(
+                        error.kind(),
+                        ErrorKind::ConnectionReset | ErrorKind::ConnectionRefused
+                    ) =>
+                {
+                    continue
+                }
+                Err(_) => break,
+            }
+        }
+        messages
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::Ipv4Addr;
+
+    use super::*;
+
+    fn loopback_socket(conditioner: LinkConditioner) -> NetSocket {
+        NetSocket::bind((Ipv4Addr::LOCALHOST, 0).into(), conditioner).unwrap()
+    }
+
+    // 反复发出到期的数据包并接收，直到超时
+    fn receive_for(sender: &mut NetSocket, receiver: &mut NetSocket, duration: Duration) -> Vec<(Instant, NetMessage)> {
+        let mut received = Vec::new();
+        let deadline = Instant::now() + duration;
+        while Instant::now() < deadline {
+            sender.flush();
+            let now = Instant::now();
+            received.extend(receiver.receive().into_iter().map(|(_, message)| (now, message)));
+            std::thread::sleep(Duration::from_millis(1));
+        }
+        received
+    }
+
+    #[test]
+    fn messages_pass_through_unconditioned_socket() {
+        let mut sender = loopback_socket(LinkConditioner::default());
+        let mut receiver = loopback_socket(LinkConditioner::default());
+        let addr = receiver.local_addr().unwrap();
+        sender.send(
+            addr,
+            &NetMessage::Inputs {
+                start: 3,
+                inputs: vec![1, 2, 15],
+                ack: 7,
+                checksums: vec![(31, u64::MAX)],
+                pauses: 1,
+            },
+        );
+        let received = receive_for(&mut sender, &mut receiver, Duration::from_millis(200));
+        let [(_, NetMessage::Inputs { start, inputs, ack, checksums, pauses })] = received.as_slice() else {
+            panic!("expected one Inputs message, got {received:?}");
+        };
+        assert_eq!((*start, inputs.as_slice(), *ack, *pauses), (3, [1, 2, 15].as_slice(), 7, 1));
+        assert_eq!(checksums, &[(31, u64::MAX)]);
+    }
+
+    #[test]
+    fn conditioner_delays_and_drops_packets() {
+        let latency = Duration::from_millis(150);
+        let conditioner = LinkConditioner {
+            latency_ms: latency.as_millis() as u64,
+            jitter_ms: 0,
+            loss: 0.5,
+        };
+        let mut sender = loopback_socket(conditioner);
+        let mut receiver = loopback_socket(LinkConditioner::default());
+        let addr = receiver.local_addr().unwrap();
+        let sent = Instant::now();
+        for time_ms in 0..200 {
+            sender.send(addr, &NetMessage::Ping { time_ms });
+        }
+        let received = receive_for(&mut sender, &mut receiver, latency + Duration::from_millis(500));
+
+        // 丢掉大约一半，其余的在延迟之后按发送顺序到达
+        assert!((40..=160).contains(&received.len()), "received {} of 200", received.len());
+        assert!(received.iter().all(|(at, _)| at.duration_since(sent) >= latency));
+        let times: Vec<u64> = received
+            .iter()
+            .map(|(_, message)| match message {
+                NetMessage::Ping { time_ms } => *time_ms,
+                other => panic!("unexpected message {other:?}"),
+            })
+            .collect();
+        assert!(times.windows(2).all(|pair| pair[0] < pair[1]));
+    }
+}
@@ -1,6 +1,26 @@
+use bevy::asset::LoadedFolder;
+use bevy::platform::collections::HashMap;
+
 use crate::prelude::*;
 use bevy_hanabi::prelude::*;
 
+// 一次性爆发类效果预先生成的发射器数量，发射器循环复用
+const EMITTER_POOL_SIZE: usize = 8;
+// 存放粒子效果数据文件（.effect，RON 格式）的资源目录，文件名即效果名
+const EFFECTS_FOLDER: &str = "effects";
+// 主推进器满推力时每秒喷出的粒子数
+const EXHAUST_RATE: f32 = 400.0;
+// 主推进器满推力时的喷射速度
+const EXHAUST_SPEED: f32 = 300.0;
+// 侧向推进器旋转时每秒喷出的粒子数
+const SIDE_THRUSTER_RATE: f32 = 60.0;
+// 侧向推进器的喷射速度
+const SIDE_THRUSTER_SPEED: f32 = 120.0;
+// 飞船速度达到该值时喷射颜色最“热”
+const EXHAUST_HOT_SPEED: f32 = 400.0;
+// 推进器强度每秒向目标值逼近的速率，避免喷射随按键突变
+const THRUSTER_RESPONSE: f32 = 12.0;
+
 // 这个插件负责在游戏中不同节点添加粒子特效
 // 为了方便，在面向 WASM 时可以轻松禁用所有粒子效果
 pub struct ParticleEffectsPlugin;
@@ -8,89 +28,515 @@ pub struct ParticleEffectsPlugin;
 impl Plugin for ParticleEffectsPlugin {
     fn build(&self, app: &mut App) {
         app.add_plugins(HanabiPlugin) // 添加 Hanabi 粒子系统插件
-            .add_systems(Update, add_thrust_particles_to_ship) // 监听新生成的飞船，附加喷射粒子
+            // 爆炸改由粒子碎片表现，精灵动画不再生成
+            .insert_resource(ExplosionStyle::Particles)
+            // 初始化时创建所有命名粒子效果，并开始加载数据文件中的效果
+            .add_systems(OnEnter(AppState::Setup), setup_particle_effects)
+            .add_systems(Update, (register_effect_files, add_thrust_particles_to_ship)) // 注册加载完成的数据文件效果；监听新生成的飞船，附加喷射粒子
             .add_systems(
                 Update,
-                update_thrust_particles.run_if(in_state(GameState::Running)), // 仅在游戏运行时更新喷射粒子
-            );
+                (update_thrust_particles, spawn_explosion_particles)
+                    .run_if(in_state(GameState::Running)), // 仅在游戏运行时更新喷射粒子和爆炸粒子
+            )
+            // 推进器的发射数量需要覆盖 Hanabi 每帧计算出的值，因此排在其后
+            .add_systems(
+                PostUpdate,
+                emit_thrust_particles
+                    .after(EffectSystems::TickSpawners)
+                    .run_if(in_state(GameState::Running)),
+            )
+            // 游戏逻辑通过触发 PlayParticleEffect 按名字播放粒子效果
+            .add_observer(play_particle_effect);
     }
 }
 
-// 给每个新创建的飞船添加一个喷射粒子效果
-fn add_thrust_particles_to_ship(
+/// 按名字播放一次粒子效果的请求
+///
+/// 以全局触发器的形式发送（`commands.trigger(...)`）。
+/// 粒子插件未启用（例如 WASM）时没有观察者响应，请求会被直接忽略
+#[derive(Event, Debug, Clone, Copy)]
+pub struct PlayParticleEffect {
+    /// 注册表中的效果名
+    pub name: &'static str,
+    /// 播放位置
+    pub position: Vec2,
+    /// 速度和尺寸的缩放系数，对声明了 "scale" 属性的效果生效
+    pub scale: f32,
+    /// 覆盖效果默认的粒子数量
+    pub count: Option<f32>,
+}
+
+impl PlayParticleEffect {
+    pub fn new(name: &'static str, position: Vec2) -> Self {
+        Self {
+            name,
+            position,
+            scale: 1.0,
+            count: None,
+        }
+    }
+}
+
+// 飞船上推进器的位置
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum Thruster {
+    Main,  // 尾部主推进器
+    Left,  // 机头左侧，向右旋转时喷气
+    Right, // 机头右侧，向左旋转时喷气
+}
+
+impl Thruster {
+    // 推进器相对飞船的位置和喷气方向（飞船本地坐标，机头朝 +Y）
+    fn placement(&self) -> (Vec3, Vec3) {
+        match self {
+            Thruster::Main => (Vec3::new(0.0, -4.0, 10.0), Vec3::NEG_Y),
+            Thruster::Left => (Vec3::new(-8.0, 6.0, 10.0), Vec3::NEG_X),
+            Thruster::Right => (Vec3::new(8.0, 6.0, 10.0), Vec3::X),
+        }
+    }
+}
+
+// 飞船推进器粒子发射器
+// intensity 为当前喷射强度（0~1），spawn_remainder 累积不足一个粒子的发射量
+#[derive(Component)]
+struct ThrusterEmitter {
+    thruster: Thruster,
+    intensity: f32,
+    spawn_remainder: f32,
+}
+
+// 一组复用的粒子发射器实体，按顺序轮流取用
+struct EmitterPool {
+    emitters: Vec<Entity>,
+    next: usize,
+}
+
+impl EmitterPool {
+    // 取出下一个发射器；如果它上一次的粒子还没消失，旧粒子会继续在原地播放完
+    fn next(&mut self) -> Option<Entity> {
+        let entity = *self.emitters.get(self.next)?;
+        self.next = (self.next + 1) % self.emitters.len();
+        Some(entity)
+    }
+}
+
+// 注册表中的一项：效果资源句柄，以及一次性效果使用的发射器池
+struct RegisteredEffect {
+    handle: Handle<EffectAsset>,
+    pool: EmitterPool,
+}
+
+/// 粒子效果注册表
+///
+/// 所有命名粒子效果在初始化时只创建一次（来自代码或 `assets/effects/*.effect` 数据文件），
+/// 之后飞船、爆炸等游戏逻辑通过名字共享同一个 `EffectAsset`，不再每次新建
+#[derive(Resource, Default)]
+pub struct ParticleEffectRegistry {
+    effects: HashMap<String, RegisteredEffect>,
+    // 正在加载的数据文件目录，加载完成后注册其中的效果
+    folder: Handle<LoadedFolder>,
+}
+
+impl ParticleEffectRegistry {
+    /// 获取指定名字的粒子效果句柄，用于挂载常驻的粒子效果（例如飞船喷射）
+    pub fn handle(&self, name: &str) -> Option<Handle<EffectAsset>> {
+        self.effects.get(name).map(|effect| effect.handle.clone())
+    }
+
+    // 注册一个效果，并为其生成 pool_size 个未激活的发射器
+    fn register(
+        &mut self,
+        commands: &mut Commands,
+        name: &str,
+        handle: Handle<EffectAsset>,
+        settings: &SpawnerSettings,
+        pool_size: usize,
+    ) {
+        // 发射器只在收到播放请求时发射
+        let spawner = EffectSpawner::new(&settings.with_emit_on_start(false));
+        let emitters = (0..pool_size)
+            .map(|_| {
+                commands
+                    .spawn((
+                        Name::new(format!("ParticleEmitter {name}")),
+                        ParticleEffect::new(handle.clone()),
+                        EffectProperties::default(),
+                        spawner,
+                        Transform::from_translation(Vec3::new(0.0, 0.0, 3.0)),
+                    ))
+                    .id()
+            })
+            .collect();
+        self.effects.insert(
+            name.to_string(),
+            RegisteredEffect {
+                handle,
+                pool: EmitterPool { emitters, next: 0 },
+            },
+        );
+    }
+}
+
+// 根据爆炸类型返回 (碎片数, 火花数, 烟雾数, 速度和尺寸的缩放系数)
+fn explosion_particles(kind: ExplosionKind) -> (f32, f32, f32, f32) {
+    match kind {
+        ExplosionKind::ShipDead => (60., 150., 40., 2.0),
+        ExplosionKind::ShipContact => (15., 40., 10., 1.0),
+        ExplosionKind::LaserOnAsteroid => (0., 20., 0., 0.6),
+        ExplosionKind::AsteroidDestroyed(AsteroidSize::Big) => (60., 30., 30., 1.6),
+        ExplosionKind::AsteroidDestroyed(AsteroidSize::Medium) => (30., 20., 15., 1.0),
+        ExplosionKind::AsteroidDestroyed(AsteroidSize::Small) => (12., 10., 6., 0.6),
+    }
+}
+
+// 创建一种径向爆散的粒子效果
+// 粒子速度和尺寸都乘以 "scale" 属性，使同一个效果可以表现不同规模的爆炸
+fn radial_burst_effect(
+    name: &str,
+    speed: (f32, f32),
+    lifetime: (f32, f32),
+    drag: f32,
+    size: f32,
+    gradient: Gradient<Vec4>,
+) -> EffectAsset {
+    let writer = ExprWriter::new();
+    let scale = writer.add_property("scale", 1.0.into());
+
+    let init_pos = SetPositionCircleModifier {
+        center: writer.lit(Vec3::ZERO).expr(),
+        axis: writer.lit(Vec3::Z).expr(),
+        radius: (writer.lit(4.0) * writer.prop(scale)).expr(),
+        dimension: ShapeDimension::Volume,
+    };
+    let init_vel = SetVelocityCircleModifier {
+        center: writer.lit(Vec3::ZERO).expr(),
+        axis: writer.lit(Vec3::Z).expr(),
+        speed: (writer.lit(speed.0).uniform(writer.lit(speed.1)) * writer.prop(scale)).expr(),
+    };
+    let init_lifetime = SetAttributeModifier::new(
+        Attribute::LIFETIME,
+        writer.lit(lifetime.0).uniform(writer.lit(lifetime.1)).expr(),
+    );
+    let init_size = SetAttributeModifier::new(
+        Attribute::SIZE,
+        (writer.lit(size) * writer.prop(scale)).expr(),
+    );
+    let update_drag = LinearDragModifier::new(writer.lit(drag).expr());
+
+    // 每次播放时再指定粒子数量
+    let spawner = SpawnerSettings::once(0.0.into());
+    EffectAsset::new(4096, spawner, writer.finish())
+        .with_name(name)
+        .init(init_pos)
+        .init(init_vel)
+        .init(init_lifetime)
+        .init(init_size)
+        .update(update_drag)
+        .render(ColorOverLifetimeModifier {
+            gradient,
+            blend: ColorBlendMode::Overwrite,
+            mask: ColorBlendMask::RGBA,
+        })
+        .render(ScreenSpaceSizeModifier)
+}
+
+// 飞船推进器粒子效果
+// 喷射方向、速度和颜色都由属性驱动，发射数量每帧由推力决定，不再重新触发发射器
+fn thruster_effect(name: &str, capacity: u32, lifetime: (f32, f32), spread: f32, size: f32) -> EffectAsset {
+    let writer = ExprWriter::new();
+    let direction = writer.add_property("direction", Vec3::NEG_Y.into());
+    let speed = writer.add_property("speed", 0.0.into());
+    let color = writer.add_property("color", Vec4::ONE.into());
+
+    let init_pos = SetPositionCircleModifier {
+        center: writer.lit(Vec3::ZERO).expr(),
+        axis: writer.lit(Vec3::Z).expr(),
+        radius: writer.lit(2.0).expr(),
+        dimension: ShapeDimension::Volume,
+    };
+    // 沿喷射方向的速度，再叠加少量平面内的随机扩散
+    let jitter = (writer.rand(VectorType::VEC3F) - writer.lit(Vec3::splat(0.5)))
+        * writer.lit(Vec3::new(spread, spread, 0.0));
+    let velocity = writer.prop(direction)
+        * writer.prop(speed)
+        * writer.lit(0.6).uniform(writer.lit(1.0))
+        + jitter;
+    let init_vel = SetAttributeModifier::new(Attribute::VELOCITY, velocity.expr());
+    let init_lifetime = SetAttributeModifier::new(
+        Attribute::LIFETIME,
+        writer.lit(lifetime.0).uniform(writer.lit(lifetime.1)).expr(),
+    );
+    let init_color = SetAttributeModifier::new(Attribute::HDR_COLOR, writer.prop(color).expr());
+
+    // 生命周期内逐渐淡出，颜色本身来自 "color" 属性
+    let mut gradient = Gradient::new();
+    gradient.add_key(0.0, Vec4::ONE);
+    gradient.add_key(1.0, Vec4::new(1.0, 1.0, 1.0, 0.0));
+
+    EffectAsset::new(capacity, SpawnerSettings::rate(0.0.into()), writer.finish())
+        .with_name(name)
+        .init(init_pos)
+        .init(init_vel)
+        .init(init_lifetime)
+        .init(init_color)
+        .render(ColorOverLifetimeModifier {
+            gradient,
+            blend: ColorBlendMode::Modulate,
+            mask: ColorBlendMask::RGBA,
+        })
+        .render(SizeOverLifetimeModifier {
+            gradient: Gradient::constant(Vec3::splat(size)),
+            screen_space_size: true,
+        }) // 粒子尺寸随生命周期保持恒定，且按屏幕空间渲染
+}
+
+// 创建代码中定义的所有粒子效果，并开始加载数据文件目录
+fn setup_particle_effects(
     mut commands: Commands,
-    mut effects: ResMut<Assets<EffectAsset>>, // 粒子效果资源集合
-    added_ships: Query<Entity, Added<Ship>>, // 查询新生成的飞船实体
+    mut effects: ResMut<Assets<EffectAsset>>,
+    asset_server: Res<AssetServer>,
 ) {
-    for ship_entity in added_ships.iter() {
-        // 飞船喷射粒子效果相关设置
-
-        let writer = ExprWriter::new();
-        let lifetime = writer.lit(0.1).expr(); // 粒子生命周期固定0.1秒
-
-        // 粒子颜色渐变，从紫色到黄色再到透明
-        let mut gradient = Gradient::new();
-        gradient.add_key(0.0, Vec4::new(0.5, 0.4, 0.7, 0.8));
-        gradient.add_key(0.5, Vec4::new(1.0, 0.8, 0.0, 0.8));
-        gradient.add_key(1.0, Vec4::ZERO);
-
-        // 粒子初始位置限制在一个锥形体积内，底面半径2，顶面半径1，高度-5
-        let init_pos = SetPositionCone3dModifier {
-            height: writer.lit(-5.0).expr(),
-            base_radius: writer.lit(2.).expr(),
-            top_radius: writer.lit(1.).expr(),
-            dimension: ShapeDimension::Volume,
-        };
+    let mut registry = ParticleEffectRegistry {
+        folder: asset_server.load_folder(EFFECTS_FOLDER),
+        ..default()
+    };
+
+    // 飞船主推进器和侧向推进器：挂载在每艘飞船上，不需要发射器池
+    for (name, effect) in [
+        ("exhaust", thruster_effect("Exhaust", 512, (0.08, 0.16), 80.0, 2.0)),
+        ("side_thruster", thruster_effect("SideThruster", 128, (0.1, 0.25), 40.0, 3.0)),
+    ] {
+        let settings = effect.spawner;
+        registry.register(&mut commands, name, effects.add(effect), &settings, 0);
+    }
+
+    // 碎片：褐色岩块，速度中等，阻力小
+    let mut debris_gradient = Gradient::new();
+    debris_gradient.add_key(0.0, Vec4::new(0.6, 0.45, 0.3, 1.0));
+    debris_gradient.add_key(0.8, Vec4::new(0.4, 0.3, 0.2, 1.0));
+    debris_gradient.add_key(1.0, Vec4::new(0.4, 0.3, 0.2, 0.0));
+    let debris = radial_burst_effect("ExplosionDebris", (40., 160.), (0.8, 1.4), 1.5, 3.0, debris_gradient);
+
+    // 火花：明亮的黄白色，速度快、寿命短
+    let mut sparks_gradient = Gradient::new();
+    sparks_gradient.add_key(0.0, Vec4::new(1.0, 1.0, 0.8, 1.0));
+    sparks_gradient.add_key(0.4, Vec4::new(1.0, 0.7, 0.1, 1.0));
+    sparks_gradient.add_key(1.0, Vec4::new(1.0, 0.2, 0.0, 0.0));
+    let sparks = radial_burst_effect("ExplosionSparks", (150., 400.), (0.2, 0.5), 4.0, 2.0, sparks_gradient);
+
+    // 烟雾：半透明灰色，缓慢扩散
+    let mut smoke_gradient = Gradient::new();
+    smoke_gradient.add_key(0.0, Vec4::new(0.5, 0.5, 0.5, 0.5));
+    smoke_gradient.add_key(1.0, Vec4::new(0.3, 0.3, 0.3, 0.0));
+    let smoke = radial_burst_effect("ExplosionSmoke", (10., 50.), (1.0, 2.0), 1.0, 8.0, smoke_gradient);
+
+    for (name, effect) in [
+        ("explosion_debris", debris),
+        ("explosion_sparks", sparks),
+        ("explosion_smoke", smoke),
+    ] {
+        let settings = effect.spawner;
+        registry.register(&mut commands, name, effects.add(effect), &settings, EMITTER_POOL_SIZE);
+    }
+
+    commands.insert_resource(registry);
+}
 
-        // 粒子初速度为球形分布，速度范围在100到400之间，中心向量为(0,1,0)
-        let init_vel = SetVelocitySphereModifier {
-            speed: writer.lit(100.0).uniform(writer.lit(400.0)).expr(),
-            center: writer.lit(Vec3::new(0.0, 1.0, 0.0)).expr(),
+// 数据文件目录加载完成后，以文件名（不含扩展名）为效果名注册其中的所有效果
+fn register_effect_files(
+    mut commands: Commands,
+    mut folder_events: EventReader<AssetEvent<LoadedFolder>>,
+    registry: Option<ResMut<ParticleEffectRegistry>>,
+    folders: Res<Assets<LoadedFolder>>,
+    effects: Res<Assets<EffectAsset>>,
+) {
+    let Some(mut registry) = registry else {
+        return;
+    };
+    for event in folder_events.read() {
+        if !event.is_loaded_with_dependencies(&registry.folder) {
+            continue;
+        }
+        let Some(folder) = folders.get(&registry.folder) else {
+            continue;
         };
+        for handle in folder.handles.iter() {
+            let Ok(handle) = handle.clone().try_typed::<EffectAsset>() else {
+                continue;
+            };
+            let (Some(path), Some(effect)) = (handle.path(), effects.get(&handle)) else {
+                continue;
+            };
+            let Some(name) = path.path().file_stem().and_then(|stem| stem.to_str()) else {
+                continue;
+            };
+            let name = name.to_string();
+            let settings = effect.spawner;
+            registry.register(&mut commands, &name, handle, &settings, EMITTER_POOL_SIZE);
+        }
+    }
+}
 
-        // 创建一个名为“Exhaust”的粒子效果资源
-        let effect = effects.add(
-            EffectAsset::new(16024, SpawnerSettings::once(10.0.into()), writer.finish())
-                .with_name("Exhaust")
-                .init(init_pos)
-                .init(init_vel)
-                .init(SetAttributeModifier::new(Attribute::LIFETIME, lifetime)) // 设置生命周期
-                .render(ColorOverLifetimeModifier {
-                    gradient,
-                    blend: ColorBlendMode::Overwrite,
-                    mask: ColorBlendMask::RGBA,
-                }) // 颜色渐变效果
-                .render(SizeOverLifetimeModifier {
-                    gradient: Gradient::constant(Vec3::splat(2.)),
-                    screen_space_size: true,
-                }), // 粒子尺寸随生命周期保持恒定，且按屏幕空间渲染
-        );
+// 响应播放请求：从对应效果的发射器池中取出发射器，移动到指定位置并重新发射
+fn play_particle_effect(
+    trigger: Trigger<PlayParticleEffect>,
+    registry: Option<ResMut<ParticleEffectRegistry>>,
+    mut emitters: Query<(&mut Transform, &mut EffectSpawner, &mut EffectProperties)>,
+) {
+    let request = trigger.event();
+    let Some(mut registry) = registry else {
+        return;
+    };
+    let Some(effect) = registry.effects.get_mut(request.name) else {
+        warn!("Unknown particle effect {:?}", request.name);
+        return;
+    };
+    let Some(emitter) = effect.pool.next() else {
+        return;
+    };
+    if let Ok((mut transform, mut spawner, mut properties)) = emitters.get_mut(emitter) {
+        transform.translation.x = request.position.x;
+        transform.translation.y = request.position.y;
+        properties.set("scale", request.scale.into());
+        if let Some(count) = request.count {
+            spawner.settings.set_count(count.into());
+        }
+        spawner.reset();
+    }
+}
 
-        // 将粒子效果作为飞船的子实体生成，位置稍微偏移
+// 监听爆炸事件，按爆炸规模请求播放碎片、火花和烟雾效果
+fn spawn_explosion_particles(
+    mut commands: Commands,
+    mut event_reader: EventReader<SpawnExplosionEvent>,
+) {
+    for event in event_reader.read() {
+        let (debris, sparks, smoke, scale) = explosion_particles(event.kind);
+        for (name, count) in [
+            ("explosion_debris", debris),
+            ("explosion_sparks", sparks),
+            ("explosion_smoke", smoke),
+        ] {
+            if count > 0.0 {
+                commands.trigger(PlayParticleEffect {
+                    name,
+                    position: Vec2::new(event.x, event.y),
+                    scale,
+                    count: Some(count),
+                });
+            }
+        }
+    }
+}
+
+// 给每个新创建的飞船挂载注册表中共享的推进器粒子效果
+fn add_thrust_particles_to_ship(
+    mut commands: Commands,
+    registry: Option<Res<ParticleEffectRegistry>>, // 粒子效果注册表
+    added_ships: Query<Entity, Added<Ship>>, // 查询新生成的飞船实体
+) {
+    let Some(registry) = registry else {
+        return;
+    };
+    let (Some(exhaust), Some(side_thruster)) =
+        (registry.handle("exhaust"), registry.handle("side_thruster"))
+    else {
+        return;
+    };
+    for ship_entity in added_ships.iter() {
+        // 将推进器粒子效果作为飞船的子实体生成
         commands.entity(ship_entity).with_children(|parent| {
-            parent.spawn((
-                ParticleEffect::new(effect),                    // 粒子效果组件
-                Transform::from_translation(Vec3::new(0.0, -4.0, 10.0)), // 位置偏移
-                ExhaustEffect,                                  // 标记为喷射效果
-            ));
+            for thruster in [Thruster::Main, Thruster::Left, Thruster::Right] {
+                let effect = if thruster == Thruster::Main {
+                    exhaust.clone()
+                } else {
+                    side_thruster.clone()
+                };
+                let (offset, _) = thruster.placement();
+                parent.spawn((
+                    ParticleEffect::new(effect),       // 粒子效果组件
+                    EffectProperties::default(),       // 每帧更新的喷射属性
+                    Transform::from_translation(offset), // 位置偏移
+                    ThrusterEmitter {
+                        thruster,
+                        intensity: 0.0,
+                        spawn_remainder: 0.0,
+                    },
+                ));
+            }
         });
     }
 }
 
-// 飞船推动时触发粒子生成
+#[allow(clippy::type_complexity)]
+// 每帧根据推力、旋转输入和飞船速度更新推进器的喷射强度、方向、速度和颜色
 fn update_thrust_particles(
-    player: Query<(&ActionState<PlayerAction>, &Children), Changed<ActionState<PlayerAction>>>, // 监听玩家动作状态和子实体
-    mut exhaust_effect: Query<&mut EffectSpawner, With<ExhaustEffect>>, // 查询喷射粒子生成器组件
+    time: Res<Time>,
+    ships: Query<(
+        &Ship,
+        &ActionState<PlayerAction>,
+        &ExternalForce,
+        &LinearVelocity,
+        &Transform,
+        &Children,
+        Has<Respawning>,
+    )>,
+    mut emitters: Query<(&mut ThrusterEmitter, &mut EffectProperties)>,
 ) {
-    for (action_state, children) in player.iter() {
-        // 如果按下了“前进”按键
-        if action_state.pressed(&PlayerAction::Forward) {
-            for child in children.iter() {
-                // 重置喷射粒子生成器，开始发射新粒子
-                if let Ok(mut initializers) = exhaust_effect.get_mut(child) {
-                    initializers.reset();
+    let dt = time.delta_secs();
+    for (ship, action_state, force, linvel, transform, children, respawning) in ships.iter() {
+        // 当前推力占满推力的比例，以及飞船速度的比例
+        let thrust = (force.force().length() / ship.thrust).clamp(0.0, 1.0);
+        let heat = (linvel.length() / EXHAUST_HOT_SPEED).min(1.0);
+        for child in children.iter() {
+            let Ok((mut emitter, mut properties)) = emitters.get_mut(child) else {
+                continue;
+            };
+            let (target, base_speed) = match emitter.thruster {
+                // 等待重生的飞船不喷射
+                _ if respawning => (0.0, SIDE_THRUSTER_SPEED),
+                Thruster::Main => (thrust, EXHAUST_SPEED),
+                Thruster::Left if action_state.pressed(&PlayerAction::RotateRight) => {
+                    (1.0, SIDE_THRUSTER_SPEED)
                 }
-            }
+                Thruster::Right if action_state.pressed(&PlayerAction::RotateLeft) => {
+                    (1.0, SIDE_THRUSTER_SPEED)
+                }
+                _ => (0.0, SIDE_THRUSTER_SPEED),
+            };
+            // 强度平滑逼近目标值
+            emitter.intensity += (target - emitter.intensity) * (THRUSTER_RESPONSE * dt).min(1.0);
+
+            let (_, direction) = emitter.thruster.placement();
+            let intensity = emitter.intensity;
+            // 喷射速度随强度增大，飞得越快喷得越急
+            let speed = base_speed * (0.3 + 0.7 * intensity) * (1.0 + 0.5 * heat);
+            // 颜色从低推力的紫色过渡到高推力高速度时的黄白色
+            let hotness = (0.6 * intensity + 0.4 * heat).min(1.0);
+            let color = Vec4::new(0.5, 0.4, 0.7, 0.8).lerp(Vec4::new(1.0, 0.9, 0.4, 0.9), hotness);
+
+            properties.set("direction", (transform.rotation * direction).into());
+            properties.set("speed", speed.into());
+            properties.set("color", color.into());
         }
     }
 }
+
+// 按当前喷射强度计算每帧的发射数量，覆盖发射器自身的计算结果
+fn emit_thrust_particles(
+    time: Res<Time>,
+    mut emitters: Query<(&mut ThrusterEmitter, &mut EffectSpawner)>,
+) {
+    let dt = time.delta_secs();
+    for (mut emitter, mut spawner) in emitters.iter_mut() {
+        let rate = match emitter.thruster {
+            Thruster::Main => EXHAUST_RATE,
+            Thruster::Left | Thruster::Right => SIDE_THRUSTER_RATE,
+        };
+        // 强度很低时停止喷射，避免松开按键后仍有零星粒子
+        let rate = if emitter.intensity < 0.05 { 0.0 } else { rate * emitter.intensity };
+        let count = rate * dt + emitter.spawn_remainder;
+        spawner.spawn_count = count.floor() as u32;
+        emitter.spawn_remainder = count.fract();
+    }
+}
@@ -0,0 +1,218 @@
+use avian2d::diagnostics::{PhysicsTotalDiagnostics, PhysicsTotalDiagnosticsPlugin};
+use bevy::diagnostic::{
+    DiagnosticPath, DiagnosticsStore, EntityCountDiagnosticsPlugin, FrameTimeDiagnosticsPlugin,
+};
+use bevy_hanabi::prelude::ParticleEffect;
+
+use crate::prelude::*;
+
+// 帧时间曲线显示的帧数
+const PERF_GRAPH_FRAMES: usize = 120;
+// 帧时间曲线的高度（像素），满高度对应 PERF_GRAPH_MAX_MS 毫秒
+const PERF_GRAPH_HEIGHT: f32 = 60.0;
+const PERF_GRAPH_MAX_MS: f64 = 50.0;
+// 统计文字的刷新间隔（秒），避免数字跳得太快看不清
+const PERF_TEXT_REFRESH_SECS: f32 = 0.25;
+
+// 性能调试面板（资源），按 F2 显示或隐藏
+// 显示帧率、帧时间曲线、各类实体数量、正在播放的音效和物理步进耗时
+#[derive(Resource, Debug)]
+pub struct PerfOverlay {
+    /// 面板是否显示
+    pub visible: bool,
+    // 统计文字刷新计时器
+    refresh_timer: Timer,
+}
+
+impl Default for PerfOverlay {
+    fn default() -> Self {
+        Self {
+            visible: false,
+            refresh_timer: Timer::from_seconds(PERF_TEXT_REFRESH_SECS, TimerMode::Repeating),
+        }
+    }
+}
+
+// 面板根节点
+#[derive(Component)]
+struct PerfOverlayRoot;
+
+// 面板统计文字
+#[derive(Component)]
+struct PerfOverlayText;
+
+// 帧时间曲线中的一根柱子，数值为从旧到新的序号
+#[derive(Component)]
+struct PerfGraphBar(usize);
+
+// 性能调试面板插件，同时注册帧时间、实体数量和物理耗时的诊断数据
+pub struct PerfOverlayPlugin;
+
+impl Plugin for PerfOverlayPlugin {
+    fn build(&self, app: &mut App) {
+        // 物理诊断需要在物理插件之后添加
+        app.add_plugins((
+            FrameTimeDiagnosticsPlugin::new(PERF_GRAPH_FRAMES),
+            EntityCountDiagnosticsPlugin,
+            PhysicsDiagnosticsPlugin,
+            PhysicsTotalDiagnosticsPlugin,
+        ))
+        .init_resource::<PerfOverlay>()
+        .add_systems(
+            Update,
+            (
+                toggle_perf_overlay,
+                (perf_text_system, perf_graph_system)
+                    .run_if(|overlay: Res<PerfOverlay>| overlay.visible),
+            )
+                .chain(),
+        );
+    }
+}
+
+// 按 F2 显示或隐藏面板
+fn toggle_perf_overlay(
+    mut commands: Commands,
+    keys: Res<ButtonInput<KeyCode>>,
+    assets: Option<Res<UiAssets>>,
+    mut overlay: ResMut<PerfOverlay>,
+    roots: Query<Entity, With<PerfOverlayRoot>>,
+) {
+    if !keys.just_pressed(KeyCode::F2) {
+        return;
+    }
+    let Some(assets) = assets else {
+        return;
+    };
+    overlay.visible = !overlay.visible;
+    if overlay.visible {
+        spawn_perf_overlay(&mut commands, &assets);
+        // 打开后立即刷新一次文字
+        let duration = overlay.refresh_timer.duration();
+        overlay.refresh_timer.set_elapsed(duration);
+    } else {
+        for entity in roots.iter() {
+            commands.entity(entity).despawn();
+        }
+    }
+}
+
+// 在左下角生成面板：统计文字和下方的帧时间曲线
+fn spawn_perf_overlay(commands: &mut Commands, assets: &UiAssets) {
+    let graph = commands
+        .spawn(Node {
+            width: Val::Px(PERF_GRAPH_FRAMES as f32 * 2.0),
+            height: Val::Px(PERF_GRAPH_HEIGHT),
+            align_items: AlignItems::FlexEnd, // 柱子从底部向上长
+            flex_direction: FlexDirection::Row,
+            margin: UiRect::top(Val::Px(6.0)),
+            ..default()
+        })
+        .id();
+    for index in 0..PERF_GRAPH_FRAMES {
+        commands.entity(graph).with_child((
+            PerfGraphBar(index),
+            Node {
+                width: Val::Px(2.0),
+                height: Val::Px(0.0),
+                ..default()
+            },
+            BackgroundColor(Color::NONE),
+        ));
+    }
+    commands
+        .spawn((
+            PerfOverlayRoot,
+            Node {
+                position_type: PositionType::Absolute,
+                left: Val::Px(10.0),
+                bottom: Val::Px(10.0),
+                flex_direction: FlexDirection::Column,
+                padding: UiRect::all(Val::Px(6.0)),
+                ..default()
+            },
+            BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.6)),
+            GlobalZIndex(15), // 盖在演示遮罩之上，控制台之下
+            children![(
+                PerfOverlayText,
+                Text::default(),
+                TextFont {
+                    font: assets.font_fira.clone(),
+                    font_size: 16.0,
+                    ..default()
+                },
+                TextColor(Color::WHITE),
+            )],
+        ))
+        .add_child(graph);
+}
+
+#[allow(clippy::type_complexity)]
+// 定时刷新统计文字
+fn perf_text_system(
+    time: Res<Time<Real>>,
+    diagnostics: Res<DiagnosticsStore>,
+    mut overlay: ResMut<PerfOverlay>,
+    asteroids: Query<(), With<Asteroid>>,
+    lasers: Query<(), With<Laser>>,
+    explosions: Query<(), With<Explosion>>,
+    particles: Query<(), With<ParticleEffect>>,
+    audio_sinks: Query<(), With<AudioSink>>,
+    mut texts: Query<&mut Text, With<PerfOverlayText>>,
+) {
+    if !overlay.refresh_timer.tick(time.delta()).just_finished() {
+        return;
+    }
+    let smoothed = |path: &DiagnosticPath| {
+        diagnostics
+            .get(path)
+            .and_then(|diagnostic| diagnostic.smoothed())
+            .unwrap_or(0.0)
+    };
+    let fps = smoothed(&FrameTimeDiagnosticsPlugin::FPS);
+    let frame_ms = smoothed(&FrameTimeDiagnosticsPlugin::FRAME_TIME);
+    let physics_ms = smoothed(PhysicsTotalDiagnostics::STEP_TIME);
+    let entities = smoothed(&EntityCountDiagnosticsPlugin::ENTITY_COUNT);
+    for mut text in texts.iter_mut() {
+        text.0 = format!(
+            "FPS {fps:>5.1}  frame {frame_ms:>5.2} ms\n\
+             physics step {physics_ms:>5.2} ms\n\
+             entities {entities:>5.0}\n\
+             asteroids {:>3}  lasers {:>3}  explosions {:>3}\n\
+             particle effects {:>3}  audio {:>3}",
+            asteroids.iter().count(),
+            lasers.iter().count(),
+            explosions.iter().count(),
+            particles.iter().count(),
+            audio_sinks.iter().count(),
+        );
+    }
+}
+
+// 每帧按帧时间历史更新曲线柱子的高度和颜色
+fn perf_graph_system(
+    diagnostics: Res<DiagnosticsStore>,
+    mut bars: Query<(&PerfGraphBar, &mut Node, &mut BackgroundColor)>,
+) {
+    let Some(frame_time) = diagnostics.get(&FrameTimeDiagnosticsPlugin::FRAME_TIME) else {
+        return;
+    };
+    // 历史记录不足时靠右对齐，最新的帧在最右边
+    let history: Vec<f64> = frame_time.values().copied().collect();
+    let offset = PERF_GRAPH_FRAMES.saturating_sub(history.len());
+    for (bar, mut node, mut color) in bars.iter_mut() {
+        let Some(ms) = bar.0.checked_sub(offset).and_then(|index| history.get(index)) else {
+            node.height = Val::Px(0.0);
+            continue;
+        };
+        node.height = Val::Px((ms / PERF_GRAPH_MAX_MS).min(1.0) as f32 * PERF_GRAPH_HEIGHT);
+        // 60 帧以内为绿色，30 帧以内为黄色，更慢为红色
+        color.0 = if *ms <= 1000.0 / 59.0 {
+            Color::srgb(0.2, 0.9, 0.3)
+        } else if *ms <= 1000.0 / 29.0 {
+            Color::srgb(1.0, 0.8, 0.2)
+        } else {
+            Color::srgb(1.0, 0.3, 0.2)
+        };
+    }
+}
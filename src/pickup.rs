@@ -0,0 +1,188 @@
+use serde::{Deserialize, Serialize};
+
+use crate::prelude::*;
+
+// 道具存在时间（秒），最后一段时间闪烁提示即将消失
+const PICKUP_LIFETIME_SECS: f32 = 8.0;
+const PICKUP_BLINK_SECS: f32 = 2.0;
+// 道具大小和碰撞半径
+const PICKUP_SIZE: f32 = 24.0;
+// 分数道具奖励的分数
+const PICKUP_POINTS: u32 = 250;
+
+// 道具种类
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PickupKind {
+    Points,    // 奖励分数
+    ExtraLife, // 奖励一条命（不超过生命上限，已满时改为奖励分数）
+}
+
+impl PickupKind {
+    // 随机选择道具种类，生命道具较稀有
+    pub fn random(rng: &mut impl Rng) -> PickupKind {
+        if rng.gen_bool(0.25) {
+            PickupKind::ExtraLife
+        } else {
+            PickupKind::Points
+        }
+    }
+
+    // 道具精灵的着色
+    fn color(&self) -> Color {
+        match self {
+            PickupKind::Points => Color::srgb(1.0, 0.85, 0.2),
+            PickupKind::ExtraLife => Color::srgb(0.3, 1.0, 0.5),
+        }
+    }
+}
+
+// 道具生成事件
+#[derive(Event)]
+pub struct PickupSpawnEvent {
+    pub kind: PickupKind,
+    pub position: Vec2,
+}
+
+// 道具组件
+#[derive(Component)]
+pub struct Pickup {
+    pub kind: PickupKind,
+    // 道具存在时间计时器，时间到后销毁
+    pub despawn_timer: Timer,
+}
+
+// 道具插件，负责道具的生成、拾取和超时消失
+pub struct PickupPlugin;
+
+impl Plugin for PickupPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<PickupSpawnEvent>()
+            .add_systems(
+                FixedUpdate,
+                (pickup_collection, pickup_timeout_system).run_if(in_state(GameState::Running)),
+            )
+            // 小行星被击毁时掉落的道具在同一步生成
+            .add_systems(
+                FixedPostUpdate,
+                spawn_pickup
+                    .before(PhysicsSet::Prepare)
+                    .run_if(in_state(GameState::Running)),
+            );
+    }
+}
+
+// 根据事件生成道具，道具缓慢漂移
+fn spawn_pickup(
+    mut commands: Commands,
+    mut pickup_spawn_events: EventReader<PickupSpawnEvent>,
+    handles: Res<SpriteAssets>,
+    mut rng: ResMut<GameRng>,
+) {
+    for event in pickup_spawn_events.read() {
+        let drift = Vec2::from_angle(rng.gen_range(0.0..std::f32::consts::TAU)) * 30.0;
+        spawn_pickup_entity(&mut commands, &handles, event.kind, event.position, drift);
+    }
+}
+
+// 生成一个道具实体，回滚和读档时也用它重建道具
+pub fn spawn_pickup_entity(
+    commands: &mut Commands,
+    handles: &SpriteAssets,
+    kind: PickupKind,
+    position: Vec2,
+    drift: Vec2,
+) -> Entity {
+    commands
+        .spawn((
+            Name::new(format!("Pickup {:?}", kind)),
+            Sprite {
+                image: handles.pickup.clone(),
+                custom_size: Some(Vec2::splat(PICKUP_SIZE)),
+                color: kind.color(),
+                ..default()
+            },
+            Transform::from_translation(position.extend(1.5)),
+            Pickup {
+                kind,
+                despawn_timer: Timer::from_seconds(PICKUP_LIFETIME_SECS, TimerMode::Once),
+            },
+            MinimapIcon::PICKUP,
+            StateScoped(AppState::Game),
+            CollisionLayers::new(GameLayer::Pickup, [GameLayer::Player]), // 只与飞船交互
+            CollidingEntities::default(),
+            RigidBody::Kinematic,
+            Collider::circle(PICKUP_SIZE / 2.0),
+            Sensor, // 传感器，不影响物理碰撞响应
+            LinearVelocity(drift),
+        ))
+        .id()
+}
+
+// 飞船碰到道具时拾取
+fn pickup_collection(
+    mut commands: Commands,
+    mut arena: ResMut<Arena>,
+    extra_life_config: Res<ExtraLifeConfig>,
+    audios: Res<AudioAssets>,
+    mut score_events: EventWriter<ScoreEvent>,
+    mut extra_life_events: EventWriter<ExtraLifeEvent>,
+    pickups: Query<(Entity, &Pickup, &CollidingEntities, &Transform)>,
+    mut ships: Query<&mut Ship>,
+) {
+    for (entity, pickup, targets, transform) in pickups.iter() {
+        // 两架飞船同时碰到时归玩家ID较小的一方；CollidingEntities 是无序集合，不能依赖它的遍历顺序
+        let Some(ship_entity) = targets
+            .iter()
+            .filter_map(|target| Some((*target, ships.get(*target).ok()?.player_id)))
+            .min_by_key(|(_, player_id)| *player_id)
+            .map(|(target, _)| target)
+        else {
+            continue;
+        };
+        let mut ship = ships.get_mut(ship_entity).unwrap();
+        match pickup.kind {
+            PickupKind::ExtraLife if ship.life < extra_life_config.max_life => {
+                ship.life += 1;
+                extra_life_events.write(ExtraLifeEvent {
+                    player_id: ship.player_id,
+                });
+            }
+            _ => {
+                arena.score += PICKUP_POINTS;
+                score_events.write(ScoreEvent {
+                    points: PICKUP_POINTS,
+                    x: transform.translation.x,
+                    y: transform.translation.y,
+                });
+            }
+        }
+        commands.spawn((
+            AudioPlayer(audios.extra_life.clone()),
+            PlaybackSettings::DESPAWN.with_speed(1.5), // 升调播放，与奖励生命区分
+            StateScoped(AppState::Game),
+        ));
+        commands.entity(entity).despawn();
+    }
+}
+
+// 道具超时消失，消失前闪烁
+fn pickup_timeout_system(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut pickups: Query<(Entity, &mut Pickup, &mut Visibility)>,
+) {
+    for (entity, mut pickup, mut visibility) in pickups.iter_mut() {
+        pickup.despawn_timer.tick(time.delta());
+        if pickup.despawn_timer.finished() {
+            commands.entity(entity).despawn();
+        } else if pickup.despawn_timer.remaining_secs() < PICKUP_BLINK_SECS {
+            let visible = ((pickup.despawn_timer.remaining_secs() * 8.0) as u32).is_multiple_of(2);
+            *visibility = if visible {
+                Visibility::Inherited
+            } else {
+                Visibility::Hidden
+            };
+        }
+    }
+}
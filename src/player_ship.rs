@@ -1,5 +1,7 @@
 use std::time::Duration;
 
+use bevy_transform_interpolation::TranslationEasingState;
+
 use crate::prelude::*;
 
 // 玩家初始生命值
@@ -8,6 +10,71 @@ pub const START_LIFE: u32 = 3;
 const INVINCIBLE_TIME: f32 = 2.0;
 // 最大累计无敌时间（秒）
 const MAX_INVINCIBLE_TIME: f32 = 5.0;
+// 低于该相对速度变化（像素/秒）的碰撞视为擦碰，不造成伤害
+const SHIP_IMPACT_MIN_SPEED: f32 = 80.0;
+// 每损失一条命对应的相对速度变化（像素/秒）
+const SHIP_IMPACT_SPEED_PER_LIFE: f32 = 800.0;
+
+// 奖励生命的配置（资源）
+#[derive(Debug, Resource, Clone)]
+pub struct ExtraLifeConfig {
+    /// 每获得多少分奖励一条命，0 表示不奖励
+    pub every: u32,
+    /// 生命值上限，达到上限后不再奖励
+    pub max_life: u32,
+}
+
+impl Default for ExtraLifeConfig {
+    fn default() -> Self {
+        Self {
+            every: 10_000,
+            max_life: 6,
+        }
+    }
+}
+
+// 飞船损失一条命（但未耗尽）后的处理方式
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum RespawnStyle {
+    /// 经典方式：飞船爆炸，等待一段时间后在出生点安全时重生
+    #[default]
+    Classic,
+    /// 原地存活：飞船留在原处并获得短暂无敌
+    InPlace,
+}
+
+// 飞船重生配置（资源）
+#[derive(Debug, Resource, Clone)]
+pub struct RespawnConfig {
+    /// 损失生命后的处理方式
+    pub style: RespawnStyle,
+    /// 爆炸后至少等待多久才重生（秒）
+    pub delay_secs: f32,
+    /// 重生点周围不能有小行星的安全半径
+    pub safe_radius: f32,
+}
+
+impl Default for RespawnConfig {
+    fn default() -> Self {
+        Self {
+            style: RespawnStyle::Classic,
+            delay_secs: 2.0,
+            safe_radius: 150.0,
+        }
+    }
+}
+
+// 正在等待重生的飞船：隐藏且不参与物理
+#[derive(Component)]
+pub struct Respawning {
+    pub timer: Timer,
+}
+
+// 奖励生命事件，HUD 据此播放动画
+#[derive(Event)]
+pub struct ExtraLifeEvent {
+    pub player_id: u32,
+}
 
 // 玩家动作分为两种枚举类型
 // PlayerAction 用于游戏中玩家飞船的操作，绑定在玩家实体上
@@ -38,6 +105,10 @@ pub struct Ship {
     pub invincible_time_secs: f32,
 }
 
+// 无敌模式（资源），开启后飞船不会受到任何伤害，供调试和测试使用
+#[derive(Resource, Debug, Default)]
+pub struct GodMode(pub bool);
+
 pub struct PlayerShipPlugin;
 
 impl Plugin for PlayerShipPlugin {
@@ -45,30 +116,47 @@ impl Plugin for PlayerShipPlugin {
         // 添加玩家输入管理插件，支持 PlayerAction 操作映射
         app.add_plugins(InputManagerPlugin::<PlayerAction>::default());
         // 游戏状态进入 Setup 时生成飞船实体
+        app.init_resource::<ExtraLifeConfig>()
+            .init_resource::<RespawnConfig>()
+            .init_resource::<GodMode>()
+            .add_event::<ExtraLifeEvent>();
         app.add_systems(OnEnter(GameState::Setup), spawn_ship)
-            // 游戏运行时处理飞船输入、阻尼、计时、无敌颜色闪烁及碰撞检测
+            // 游戏运行时按固定时间步处理飞船输入、阻尼、计时及碰撞检测
             .add_systems(
-                Update,
+                FixedUpdate,
                 (
                     ship_input_system,
                     ship_dampening_system,
                     ship_timers_system,
-                    ship_invincible_color,
                     ship_asteroid_collision,
+                    extra_life_system,
+                    ship_respawn_system,
                 )
                     .run_if(in_state(GameState::Running)),
+            )
+            // 无敌颜色闪烁只是表现效果，每帧更新
+            .add_systems(
+                Update,
+                ship_invincible_color.run_if(in_state(GameState::Running)),
             );
     }
 }
 
-// 标记组件，用于更新喷射粒子特效的速度
-#[derive(Component)]
-pub struct ExhaustEffect;
+// 生成玩家1的飞船实体，由键盘操作
+pub fn spawn_ship(mut commands: Commands, handles: Res<SpriteAssets>) {
+    let ship = spawn_player_ship(&mut commands, &handles, 1, ship_spawn_point(1));
+    commands.entity(ship).insert(keyboard_input_map());
+}
 
-// 生成玩家飞船实体
-fn spawn_ship(mut commands: Commands, handles: Res<SpriteAssets>) {
-    // 定义键盘和手柄按键与玩家动作的映射关系
-    let input_map = InputMap::new([
+// 飞船的出生点：玩家1在中心，其他玩家依次排在左边，重生时也回到这里
+// 多艘飞船如果在同一点同时重生，状态会完全相同，物理引擎按实体顺序处理它们与同一颗小行星的接触，联机双方的结果就可能不同
+pub fn ship_spawn_point(player_id: u32) -> Vec2 {
+    Vec2::new(-60.0 * player_id.saturating_sub(1) as f32, 0.0)
+}
+
+// 定义键盘按键与玩家动作的映射关系
+pub fn keyboard_input_map() -> InputMap<PlayerAction> {
+    InputMap::new([
         (PlayerAction::Forward, KeyCode::KeyW),
         (PlayerAction::Forward, KeyCode::ArrowUp),
         (PlayerAction::RotateLeft, KeyCode::KeyA),
@@ -76,40 +164,52 @@ fn spawn_ship(mut commands: Commands, handles: Res<SpriteAssets>) {
         (PlayerAction::RotateRight, KeyCode::KeyD),
         (PlayerAction::RotateRight, KeyCode::ArrowRight),
         (PlayerAction::Fire, KeyCode::Space),
-    ]);
+    ])
+}
+
+// 在指定位置生成一艘飞船并返回实体
+// 飞船通过 ActionState<PlayerAction> 操作，可以来自按键映射，也可以由 AI 写入
+pub fn spawn_player_ship(
+    commands: &mut Commands,
+    handles: &SpriteAssets,
+    player_id: u32,
+    position: Vec2,
+) -> Entity {
     // 创建无敌计时器，初始状态为已计时完成（无敌关闭）
     let mut invincible_timer = Timer::from_seconds(INVINCIBLE_TIME, TimerMode::Once);
     invincible_timer.tick(Duration::from_secs_f32(INVINCIBLE_TIME));
 
     commands
         .spawn((
-            Name::new("PlayerShip"), // 实体名称
+            Name::new(format!("PlayerShip {player_id}")), // 实体名称
             Sprite {
                 image: handles.player_ship.clone(),
                 custom_size: Some(Vec2::new(30., 20.)),
                 ..default()
             },
-            Transform::from_translation(Vec3::new(0.0, 0.0, 1.0)), // 初始位置
+            Transform::from_translation(position.extend(1.0)), // 初始位置
             Ship {
                 rotation_speed: 3.0,       // 旋转速度
                 thrust: 300000.0,          // 推力
                 life: START_LIFE,          // 生命值
                 cannon_timer: Timer::from_seconds(0.2, TimerMode::Once), // 炮火冷却
-                player_id: 1,              // 玩家ID
+                player_id,                 // 玩家ID
                 invincible_timer,          // 无敌计时器
                 invincible_time_secs: 0.0, // 累计无敌时间
             },
             StateScoped(AppState::Game),                            // 状态标签
-            CollisionLayers::new(GameLayer::Player, [GameLayer::Asteroid]), // 碰撞图层
+            MinimapIcon::SHIP,                                      // 在小地图上显示
+            CollisionLayers::new(GameLayer::Player, [GameLayer::Asteroid, GameLayer::Pickup]), // 碰撞图层
             CollidingEntities::default(),                           // 碰撞实体列表
             RigidBody::Dynamic,                                     // 物理刚体类型
             Collider::circle(13.5),                                 // 碰撞体为圆形，半径13.5
             ExternalForce::default(),                               // 外力组件
             LinearVelocity::ZERO,                                   // 初始线速度为零
             AngularVelocity::ZERO,                                  // 初始角速度为零
-            input_map,                                              // 输入映射
+            ActionState::<PlayerAction>::default(),                 // 操作状态
         ))
-        .observe(on_ship_damage); // 监听飞船受伤事件
+        .observe(on_ship_damage) // 监听飞船受伤事件
+        .id()
 }
 
 // 飞船阻尼系统，逐渐减缓速度（模拟摩擦）
@@ -134,7 +234,7 @@ fn ship_timers_system(time: Res<Time>, mut ship: Query<&mut Ship>) {
 
 #[allow(clippy::type_complexity)]
 // 处理玩家输入，控制飞船移动和开火
-fn ship_input_system(
+pub fn ship_input_system(
     mut laser_spawn_events: EventWriter<LaserSpawnEvent>,
     mut query: Query<(
         &ActionState<PlayerAction>,
@@ -143,9 +243,12 @@ fn ship_input_system(
         &mut AngularVelocity,
         &Transform,
         &mut Ship,
-    )>,
+    ), Without<Respawning>>,
 ) {
-    for (action_state, mut force, linvel, mut angvel, transform, mut ship) in query.iter_mut() {
+    // 按玩家ID的顺序处理，激光的生成顺序与飞船在查询中的顺序无关（联机回滚后两边的存储顺序可能不同）
+    let mut ships: Vec<_> = query.iter_mut().collect();
+    ships.sort_by_key(|(.., ship)| ship.player_id);
+    for (action_state, mut force, linvel, mut angvel, transform, mut ship) in ships {
         // 判断前进键是否按下
         let thrust = if action_state.pressed(&PlayerAction::Forward) {
             1.0
@@ -181,20 +284,81 @@ fn ship_input_system(
 }
 
 // 飞船与小行星碰撞检测系统
+// 伤害由碰撞冲量换算出的相对速度变化决定：轻轻擦碰不受伤，撞得越狠伤害越高
 fn ship_asteroid_collision(
     mut commands: Commands,
-    ship_collisions: Query<(Entity, &CollidingEntities), With<Ship>>,
-    is_asteroid: Query<(), With<Asteroid>>,
+    collisions: Collisions,
+    ship_collisions: Query<(Entity, &CollidingEntities, &ComputedMass, &Ship)>,
+    asteroids: Query<(&ComputedMass, &Transform), With<Asteroid>>,
+) {
+    // 按玩家编号处理，受伤事件的触发顺序不依赖查询的遍历顺序
+    let mut ships: Vec<_> = ship_collisions.iter().collect();
+    ships.sort_by_key(|(.., ship)| ship.player_id);
+    for (ship, targets, ship_mass, _) in ships {
+        // 飞船与小行星碰撞
+        // 小行星不受影响，只有飞船受到伤害
+        // 爆炸特效由受伤系统处理
+        // 同时撞上多颗小行星时只按最猛烈的一次计算伤害；
+        // CollidingEntities 是无序集合，不能依赖它的遍历顺序，否则同一种子的结果不可复现
+        let hardest = targets
+            .iter()
+            .filter_map(|target| {
+                let (asteroid_mass, asteroid_transform) = asteroids.get(*target).ok()?;
+                let contact = collisions.get(ship, *target)?;
+                // 冲量 × (1/m1 + 1/m2) 即两者沿法线方向相对速度的变化量
+                let impact_speed = contact.total_normal_impulse_magnitude()
+                    * (ship_mass.inverse() + asteroid_mass.inverse());
+                Some((impact_speed, asteroid_transform.translation.truncate()))
+            })
+            .max_by(|(a, _), (b, _)| a.total_cmp(b));
+        let Some((impact_speed, point)) = hardest else {
+            continue;
+        };
+        if impact_speed < SHIP_IMPACT_MIN_SPEED {
+            continue;
+        }
+        commands.trigger_targets(
+            Damage {
+                amount: impact_speed / SHIP_IMPACT_SPEED_PER_LIFE,
+                source: DamageSource::Impact,
+                point,
+            },
+            ship,
+        );
+    }
+}
+
+// 分数每跨过一个奖励门槛，给每架飞船加一条命（不超过上限），并播放音效
+fn extra_life_system(
+    mut commands: Commands,
+    mut arena: ResMut<Arena>,
+    config: Res<ExtraLifeConfig>,
+    audios: Res<AudioAssets>,
+    mut extra_life_events: EventWriter<ExtraLifeEvent>,
+    mut ships: Query<&mut Ship>,
 ) {
-    for (ship, targets) in ship_collisions.iter() {
-        for target in targets.iter() {
-            // 飞船与小行星碰撞
-            // 小行星不受影响，只有飞船受到伤害
-            // 爆炸特效由受伤系统处理
-            if is_asteroid.contains(*target) {
-                commands.trigger_targets(Damage, ship);
+    if !arena.is_changed() || config.every == 0 {
+        return;
+    }
+    while arena.score >= (arena.extra_lives_awarded + 1) * config.every {
+        arena.extra_lives_awarded += 1;
+        let mut awarded = false;
+        for mut ship in ships.iter_mut() {
+            if ship.life < config.max_life {
+                ship.life += 1;
+                awarded = true;
+                extra_life_events.write(ExtraLifeEvent {
+                    player_id: ship.player_id,
+                });
             }
         }
+        if awarded {
+            commands.spawn((
+                AudioPlayer(audios.extra_life.clone()),
+                PlaybackSettings::DESPAWN.with_speed(0.5), // 降调播放，与射击音效区分
+                StateScoped(AppState::Game),
+            ));
+        }
     }
 }
 
@@ -203,34 +367,68 @@ fn on_ship_damage(
     trigger: Trigger<Damage>,
     mut commands: Commands,
     mut next_state: ResMut<NextState<GameState>>,
+    mut combo: ResMut<Combo>,
     mut explosion_spawn_events: EventWriter<SpawnExplosionEvent>,
-    mut ships: Query<(&mut Ship, &Transform)>,
+    respawn_config: Res<RespawnConfig>,
+    god_mode: Res<GodMode>,
+    mut ships: Query<(Entity, &mut Ship, &Transform, Has<Respawning>)>,
 ) {
+    if god_mode.0 {
+        return;
+    }
     let ship_entity = trigger.target();
-    let (mut ship, ship_transform) = ships
+    // 多艘飞船（如 AI 僚机）时，所有飞船都耗尽生命才结束游戏
+    let others_alive = ships
+        .iter()
+        .any(|(entity, other, _, _)| entity != ship_entity && other.life > 0);
+    let (_, mut ship, ship_transform, respawning) = ships
         .get_mut(ship_entity)
         .expect("Missing Ship and Transform on damage trigger");
+    // 等待重生的飞船不会再受伤
+    if respawning {
+        return;
+    }
 
     // 只有在无敌计时器结束时才允许扣血
     if ship.invincible_timer.finished() {
         ship.invincible_time_secs = 0.0;
-        ship.life -= 1; // 扣除1点生命值
+        // 按伤害扣除生命值，至少1点
+        let lost = (trigger.event().amount.ceil() as u32).clamp(1, ship.life);
+        ship.life -= lost;
+        combo.reset(); // 受伤中断连击
         if ship.life == 0 {
-            // 生命归零，生成飞船死亡爆炸特效并销毁实体，切换游戏结束状态
+            // 生命归零，生成飞船死亡爆炸特效并销毁实体，没有其它飞船时切换游戏结束状态
             explosion_spawn_events.write(SpawnExplosionEvent {
                 kind: ExplosionKind::ShipDead,
                 x: ship_transform.translation.x,
                 y: ship_transform.translation.y,
             });
             commands.entity(ship_entity).despawn();
-            next_state.set(GameState::Over);
-        } else {
+            if !others_alive {
+                next_state.set(GameState::Over);
+            }
+        } else if respawn_config.style == RespawnStyle::InPlace {
             // 生命未归零，生成飞船受伤接触爆炸特效
             explosion_spawn_events.write(SpawnExplosionEvent {
                 kind: ExplosionKind::ShipContact,
                 x: ship_transform.translation.x,
                 y: ship_transform.translation.y,
             });
+        } else {
+            // 经典方式：飞船爆炸后隐藏并移出物理模拟，等待重生
+            explosion_spawn_events.write(SpawnExplosionEvent {
+                kind: ExplosionKind::ShipDead,
+                x: ship_transform.translation.x,
+                y: ship_transform.translation.y,
+            });
+            commands.entity(ship_entity).insert((
+                Respawning {
+                    timer: Timer::from_seconds(respawn_config.delay_secs, TimerMode::Once),
+                },
+                Visibility::Hidden,
+                ColliderDisabled,
+                RigidBodyDisabled,
+            ));
         }
         // 重置无敌计时器
         ship.invincible_timer.reset();
@@ -243,6 +441,80 @@ fn on_ship_damage(
     }
 }
 
+#[allow(clippy::type_complexity)]
+// 等待结束后，若出生点安全半径内没有小行星就让飞船在出生点重生并获得短暂无敌；
+// 否则在出生点画出闪烁的圆圈，提示玩家正在等待清场
+fn ship_respawn_system(
+    mut commands: Commands,
+    time: Res<Time>,
+    config: Res<RespawnConfig>,
+    mut gizmos: Gizmos,
+    asteroids: Query<&Transform, (With<Asteroid>, Without<Ship>)>,
+    mut ships: Query<(
+        Entity,
+        &mut Respawning,
+        &mut Ship,
+        &mut Transform,
+        &mut Position,
+        &mut Rotation,
+        &mut LinearVelocity,
+        &mut AngularVelocity,
+        &mut ExternalForce,
+        Option<&mut TranslationEasingState>,
+    )>,
+) {
+    for (
+        entity,
+        mut respawning,
+        mut ship,
+        mut transform,
+        mut position,
+        mut rotation,
+        mut linvel,
+        mut angvel,
+        mut force,
+        easing,
+    ) in ships.iter_mut()
+    {
+        respawning.timer.tick(time.delta());
+        if !respawning.timer.finished() {
+            continue;
+        }
+        let spawn_point = ship_spawn_point(ship.player_id);
+        let clear = asteroids.iter().all(|asteroid| {
+            asteroid.translation.truncate().distance(spawn_point) > config.safe_radius
+        });
+        if !clear {
+            let alpha = 0.3 + 0.5 * (time.elapsed_secs() * 6.0).sin().abs();
+            gizmos.circle_2d(
+                Isometry2d::from_translation(spawn_point),
+                config.safe_radius,
+                Color::srgba(1.0, 0.4, 0.2, alpha),
+            );
+            continue;
+        }
+
+        // 在出生点静止重生，朝向正上方
+        position.0 = spawn_point;
+        *rotation = Rotation::default();
+        transform.translation = spawn_point.extend(transform.translation.z);
+        transform.rotation = Quat::IDENTITY;
+        linvel.0 = Vec2::ZERO;
+        angvel.0 = 0.0;
+        force.clear();
+        // 重生是瞬移，清除插值起点，避免渲染时从阵亡位置滑到出生点
+        if let Some(mut easing) = easing {
+            easing.start = None;
+        }
+        ship.invincible_time_secs = 0.0;
+        ship.invincible_timer.reset();
+        commands
+            .entity(entity)
+            .remove::<(Respawning, ColliderDisabled, RigidBodyDisabled)>()
+            .insert(Visibility::Inherited);
+    }
+}
+
 // 飞船与小行星接触后会短暂无敌
 // 该系统通过让飞船闪烁红色显示无敌状态
 // 通过调整精灵的alpha值实现“闪烁”效果
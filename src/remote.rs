@@ -0,0 +1,233 @@
+use bevy::remote::{error_codes, http::RemoteHttpPlugin, BrpError, BrpResult, RemotePlugin};
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+use crate::prelude::*;
+
+// 远程控制插件（仅调试构建）
+//
+// 在 Bevy 远程协议（BRP，默认监听 http://127.0.0.1:15702）的通用 ECS 方法之外，
+// 提供以下游戏专用方法，方便测试人员和外部工具用脚本布置场景：
+//
+//   supernova/spawn_asteroid  {"size": "big", "x": 100, "y": 200, "material": "iron", "vx": 0, "vy": 0, "angvel": 0}
+//       生成一颗小行星，size 为 big/medium/small，material 默认为 rock，速度默认为 0；只能在游戏运行中使用
+//   supernova/set_state       {"state": "menu" | "game" | "paused"}
+//       切换到主菜单、开始或继续游戏、暂停游戏；游戏结束后需要先回到主菜单才能开始新的一局，
+//       返回切换前的状态（previous）和游戏状态（game_state，不在游戏中时为 null）
+//   supernova/get_score
+//       返回当前状态、分数、波次和每艘飞船的生命
+//   supernova/set_life        {"life": 5, "player": 1}
+//       设置飞船生命（至少1），player 默认为 1
+//   supernova/god_mode        {"enabled": true}
+//       开关无敌模式，不带参数时切换，返回当前是否开启
+//   supernova/clear_field
+//       清除场上所有小行星，返回清除的数量
+//
+// 使用 curl 调用的例子：
+//
+//   curl -s -X POST http://127.0.0.1:15702 -H 'Content-Type: application/json' \
+//     -d '{"jsonrpc": "2.0", "id": 1, "method": "supernova/spawn_asteroid", "params": {"size": "big", "x": 100, "y": 200}}'
+//   curl -s -X POST http://127.0.0.1:15702 -d '{"jsonrpc": "2.0", "id": 2, "method": "supernova/get_score"}'
+pub struct RemoteControlPlugin;
+
+impl Plugin for RemoteControlPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins(
+            RemotePlugin::default()
+                .with_method("supernova/spawn_asteroid", spawn_asteroid_method)
+                .with_method("supernova/set_state", set_state_method)
+                .with_method("supernova/get_score", get_score_method)
+                .with_method("supernova/set_life", set_life_method)
+                .with_method("supernova/god_mode", god_mode_method)
+                .with_method("supernova/clear_field", clear_field_method),
+        )
+        .add_plugins(RemoteHttpPlugin::default()); // HTTP远程连接支持
+    }
+}
+
+#[derive(Deserialize)]
+struct SpawnAsteroidParams {
+    size: String,
+    #[serde(default)]
+    material: Option<String>,
+    x: f32,
+    y: f32,
+    #[serde(default)]
+    vx: f32,
+    #[serde(default)]
+    vy: f32,
+    #[serde(default)]
+    angvel: f32,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum RemoteState {
+    Menu,
+    Game,
+    Paused,
+}
+
+#[derive(Deserialize)]
+struct SetStateParams {
+    state: RemoteState,
+}
+
+#[derive(Deserialize)]
+struct SetLifeParams {
+    life: u32,
+    #[serde(default = "default_player")]
+    player: u32,
+}
+
+fn default_player() -> u32 {
+    1
+}
+
+#[derive(Deserialize, Default)]
+struct GodModeParams {
+    enabled: Option<bool>,
+}
+
+// 解析请求参数，没有参数时按空对象处理
+fn parse_params<T: for<'de> Deserialize<'de>>(params: Option<Value>) -> Result<T, BrpError> {
+    serde_json::from_value(params.unwrap_or_else(|| json!({}))).map_err(invalid_params)
+}
+
+fn invalid_params(message: impl ToString) -> BrpError {
+    BrpError {
+        code: error_codes::INVALID_PARAMS,
+        message: message.to_string(),
+        data: None,
+    }
+}
+
+fn require_running(game_state: &Option<Res<State<GameState>>>) -> Result<(), BrpError> {
+    if game_state
+        .as_ref()
+        .is_some_and(|state| *state.get() == GameState::Running)
+    {
+        Ok(())
+    } else {
+        Err(BrpError::internal("game is not running"))
+    }
+}
+
+// supernova/spawn_asteroid：通过 AsteroidSpawnEvent 生成小行星
+fn spawn_asteroid_method(
+    In(params): In<Option<Value>>,
+    game_state: Option<Res<State<GameState>>>,
+    mut asteroid_spawn_events: EventWriter<AsteroidSpawnEvent>,
+) -> BrpResult {
+    let params: SpawnAsteroidParams = parse_params(params)?;
+    let size = AsteroidSize::from_name(&params.size)
+        .ok_or_else(|| invalid_params(format!("unknown asteroid size `{}`", params.size)))?;
+    let material = match &params.material {
+        Some(name) => AsteroidMaterial::from_name(name)
+            .ok_or_else(|| invalid_params(format!("unknown asteroid material `{name}`")))?,
+        None => AsteroidMaterial::Rock,
+    };
+    require_running(&game_state)?;
+    asteroid_spawn_events.write(AsteroidSpawnEvent {
+        size,
+        material,
+        x: params.x,
+        y: params.y,
+        vx: params.vx,
+        vy: params.vy,
+        angvel: params.angvel,
+    });
+    Ok(json!({ "size": size.to_string(), "material": material.to_string() }))
+}
+
+// supernova/set_state：切换主菜单、游戏和暂停
+fn set_state_method(
+    In(params): In<Option<Value>>,
+    app_state: Res<State<AppState>>,
+    game_state: Option<Res<State<GameState>>>,
+    mut next_app_state: ResMut<NextState<AppState>>,
+    mut next_game_state: ResMut<NextState<GameState>>,
+) -> BrpResult {
+    let params: SetStateParams = parse_params(params)?;
+    let game_state = game_state.map(|state| *state.get());
+    match params.state {
+        RemoteState::Menu => next_app_state.set(AppState::Menu),
+        // 不在游戏中时开始新的一局，暂停中则继续游戏，已在游戏中则不变
+        RemoteState::Game => match game_state {
+            None => next_app_state.set(AppState::Game),
+            Some(GameState::Paused) => next_game_state.set(GameState::Running),
+            Some(GameState::Setup | GameState::Running) => {}
+            Some(GameState::Over) => {
+                return Err(BrpError::internal("game is over, switch to menu first"));
+            }
+        },
+        RemoteState::Paused => match game_state {
+            Some(GameState::Running) => next_game_state.set(GameState::Paused),
+            Some(GameState::Paused) => {}
+            _ => return Err(BrpError::internal("game is not running")),
+        },
+    }
+    Ok(json!({
+        "previous": format!("{:?}", app_state.get()),
+        "game_state": game_state.map(|state| format!("{state:?}")),
+    }))
+}
+
+// supernova/get_score：返回状态、分数、波次和每艘飞船的生命
+fn get_score_method(
+    In(_): In<Option<Value>>,
+    app_state: Res<State<AppState>>,
+    game_state: Option<Res<State<GameState>>>,
+    arena: Option<Res<Arena>>,
+    wave: Option<Res<Wave>>,
+    ships: Query<&Ship>,
+) -> BrpResult {
+    let mut ships: Vec<_> = ships.iter().map(|ship| (ship.player_id, ship.life)).collect();
+    ships.sort();
+    Ok(json!({
+        "state": format!("{:?}", app_state.get()),
+        "game_state": game_state.map(|state| format!("{:?}", state.get())),
+        "score": arena.map_or(0, |arena| arena.score),
+        "wave": wave.map(|wave| wave.index + 1),
+        "ships": ships
+            .into_iter()
+            .map(|(player, life)| json!({ "player": player, "life": life }))
+            .collect::<Vec<_>>(),
+    }))
+}
+
+// supernova/set_life：设置指定玩家飞船的生命
+fn set_life_method(In(params): In<Option<Value>>, mut ships: Query<&mut Ship>) -> BrpResult {
+    let params: SetLifeParams = parse_params(params)?;
+    if params.life == 0 {
+        return Err(invalid_params("life must be at least 1"));
+    }
+    let mut ship = ships
+        .iter_mut()
+        .find(|ship| ship.player_id == params.player)
+        .ok_or_else(|| BrpError::internal(format!("no ship for player {}", params.player)))?;
+    ship.life = params.life;
+    Ok(json!({ "player": params.player, "life": ship.life }))
+}
+
+// supernova/god_mode：开关无敌模式
+fn god_mode_method(In(params): In<Option<Value>>, mut god_mode: ResMut<GodMode>) -> BrpResult {
+    let params: GodModeParams = parse_params(params)?;
+    god_mode.0 = params.enabled.unwrap_or(!god_mode.0);
+    info!("God mode: {}", god_mode.0);
+    Ok(json!({ "enabled": god_mode.0 }))
+}
+
+// supernova/clear_field：清除场上所有小行星
+fn clear_field_method(
+    In(_): In<Option<Value>>,
+    mut commands: Commands,
+    asteroids: Query<Entity, With<Asteroid>>,
+) -> BrpResult {
+    let mut cleared = 0;
+    for entity in asteroids.iter() {
+        commands.entity(entity).despawn();
+        cleared += 1;
+    }
+    Ok(json!({ "cleared": cleared }))
+}
@@ -0,0 +1,316 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::prelude::*;
+
+// 存档格式版本，格式有不兼容的改动时加一；版本不一致的存档不能继续
+pub const SAVE_VERSION: u32 = 1;
+// 默认存档文件（相对于工作目录）
+const SAVE_DEFAULT_PATH: &str = "savegame.json";
+
+// 对局存档
+//
+// 暂停菜单选择 Save & Quit 时，把进行中的对局写入一个 JSON 文件后回到主菜单；
+// 主菜单在存档有效时显示 Continue，选择后按存档重建对局，恢复成功后删除存档。
+//
+// 存档记录游戏模式和对局状态（MatchState，见 match_state.rs），浮点数按原值写入，读回后完全相同。
+// 没有使用 Bevy 的场景格式：实体带有观察者、资源句柄和物理引擎的内部组件，读档时总要通过各自的生成函数重建。
+// 联机对局不能存档。
+
+// 存档设置（资源）
+#[derive(Resource, Debug, Clone)]
+pub struct SaveConfig {
+    /// 存档文件路径（--save-file 路径）
+    pub path: PathBuf,
+}
+
+impl Default for SaveConfig {
+    fn default() -> Self {
+        Self {
+            path: PathBuf::from(SAVE_DEFAULT_PATH),
+        }
+    }
+}
+
+// 暂停菜单选择 Save & Quit 的事件
+#[derive(Event)]
+pub struct SaveAndQuitEvent;
+
+// 等待恢复的存档（资源），主菜单选择 Continue 时插入，进入游戏后恢复并移除
+#[derive(Resource, Debug)]
+pub struct RestoreGame(pub SaveGame);
+
+// 存档记录的游戏模式
+// 与观战协议的 SnapshotMode 分开定义，任何一方的格式改动都不会影响另一方
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SavedMode {
+    Classic,
+    Widescreen,
+    BigArena,
+}
+
+impl From<GameMode> for SavedMode {
+    fn from(mode: GameMode) -> Self {
+        match mode {
+            GameMode::Classic => SavedMode::Classic,
+            GameMode::Widescreen => SavedMode::Widescreen,
+            GameMode::BigArena => SavedMode::BigArena,
+        }
+    }
+}
+
+impl From<SavedMode> for GameMode {
+    fn from(mode: SavedMode) -> Self {
+        match mode {
+            SavedMode::Classic => GameMode::Classic,
+            SavedMode::Widescreen => GameMode::Widescreen,
+            SavedMode::BigArena => GameMode::BigArena,
+        }
+    }
+}
+
+// 存档文件的内容
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SaveGame {
+    /// 存档格式版本，见 SAVE_VERSION
+    pub version: u32,
+    pub mode: SavedMode,
+    pub state: MatchState,
+}
+
+impl SaveGame {
+    pub fn new(mode: GameMode, state: MatchState) -> Self {
+        Self {
+            version: SAVE_VERSION,
+            mode: mode.into(),
+            state,
+        }
+    }
+
+    // 读取存档，文件不存在、格式错误或版本不一致时返回原因
+    pub fn load(path: &Path) -> Result<SaveGame, String> {
+        let text = fs::read_to_string(path).map_err(|error| format!("cannot read {}: {error}", path.display()))?;
+        // 先只检查版本，其他版本的存档结构可能完全不同
+        let value: serde_json::Value =
+            serde_json::from_str(&text).map_err(|error| format!("invalid save file {}: {error}", path.display()))?;
+        let version = value.get("version").and_then(serde_json::Value::as_u64);
+        if version != Some(u64::from(SAVE_VERSION)) {
+            return Err(format!(
+                "save file {} has version {version:?}, expected {SAVE_VERSION}",
+                path.display()
+            ));
+        }
+        serde_json::from_value(value).map_err(|error| format!("invalid save file {}: {error}", path.display()))
+    }
+
+    // 写入存档：先写临时文件再改名，写到一半失败也不会损坏已有的存档
+    pub fn write(&self, path: &Path) -> Result<(), String> {
+        let text = serde_json::to_string_pretty(self).map_err(|error| error.to_string())?;
+        let temp_path = path.with_extension("tmp");
+        fs::write(&temp_path, text)
+            .and_then(|_| fs::rename(&temp_path, path))
+            .map_err(|error| format!("cannot write {}: {error}", path.display()))
+    }
+}
+
+// 存档插件：暂停菜单的 Save & Quit 和主菜单的 Continue
+pub struct SavePlugin;
+
+impl Plugin for SavePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<SaveAndQuitEvent>()
+            .add_systems(Update, save_game_system.run_if(in_state(GameState::Paused)))
+            // 等 Setup 阶段生成的场地和飞船就位后再用存档覆盖
+            .add_systems(
+                Update,
+                restore_game_system.run_if(in_state(GameState::Setup).and(resource_exists::<RestoreGame>)),
+            );
+    }
+}
+
+// 保存当前对局并回到主菜单；写入失败时留在暂停菜单
+fn save_game_system(
+    mut events: EventReader<SaveAndQuitEvent>,
+    config: Res<SaveConfig>,
+    game_mode: Res<GameMode>,
+    reader: MatchStateReader,
+    mut next_app_state: ResMut<NextState<AppState>>,
+) {
+    if events.read().last().is_none() {
+        return;
+    }
+    let Some(state) = reader.capture() else {
+        warn!("Cannot save: the first wave has not started yet");
+        return;
+    };
+    let save = SaveGame::new(*game_mode, state);
+    match save.write(&config.path) {
+        Ok(()) => {
+            info!(
+                "Saved game to {}: wave {}, score {}",
+                config.path.display(),
+                save.state.wave + 1,
+                save.state.score
+            );
+            next_app_state.set(AppState::Menu);
+        }
+        Err(error) => error!("Cannot save: {error}"),
+    }
+}
+
+// 用存档覆盖刚开始的对局：场地、随机数、连击和波次，重建飞船、小行星、激光和道具
+fn restore_game_system(
+    mut commands: Commands,
+    restore: Res<RestoreGame>,
+    config: Res<SaveConfig>,
+    handles: Res<SpriteAssets>,
+    sprite_colliders: Res<SpriteColliders>,
+    mut arena: ResMut<Arena>,
+    mut rng: ResMut<GameRng>,
+    mut combo: ResMut<Combo>,
+    mut theme: ResMut<CurrentBackgroundTheme>,
+    mut start_wave_events: ResMut<Events<StartWave>>,
+    wells: Query<Entity, With<GravityWell>>,
+    ships: Query<Entity, With<Ship>>,
+) {
+    let save = &restore.0.state;
+    save.restore_resources(&mut arena, &mut rng, &mut combo);
+
+    // 丢弃开局的第一波，直接布置存档中的波次并恢复剩余时间
+    start_wave_events.clear();
+    let index = save.wave % WAVES.len();
+    lay_out_wave(&mut commands, &handles, arena.size, &mut theme, &wells, index);
+    commands.insert_resource(Wave {
+        index,
+        timer: save.wave_timer.to_timer(),
+    });
+
+    // 替换开局生成的飞船；玩家1仍由键盘操作（--autopilot 时由 AI 接管），僚机恢复 AI 驾驶
+    for entity in ships.iter() {
+        commands.entity(entity).despawn();
+    }
+    for saved in &save.ships {
+        let ship = saved.spawn(&mut commands, &handles);
+        if saved.player_id == 1 {
+            commands.entity(ship).insert(keyboard_input_map());
+        } else if saved.ai {
+            commands.entity(ship).insert(AiPilot::default());
+        }
+    }
+    for saved in &save.asteroids {
+        saved.spawn(&mut commands, &handles, &sprite_colliders);
+    }
+    for saved in &save.lasers {
+        saved.spawn(&mut commands, &handles);
+    }
+    for saved in &save.pickups {
+        saved.spawn(&mut commands, &handles);
+    }
+
+    info!(
+        "Restored saved game: wave {}, score {}, {} asteroids",
+        index + 1,
+        save.score,
+        save.asteroids.len()
+    );
+    commands.remove_resource::<RestoreGame>();
+    // 存档只能继续一次
+    if let Err(error) = fs::remove_file(&config.path) {
+        warn!("Cannot remove {}: {error}", config.path.display());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::ecs::system::RunSystemOnce;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    use super::*;
+
+    fn empty_save(version: u32) -> SaveGame {
+        let timer = SavedTimer::from(&Timer::from_seconds(1.0, TimerMode::Once));
+        SaveGame {
+            version,
+            mode: SavedMode::Classic,
+            state: MatchState {
+                score: 120,
+                extra_lives_awarded: 0,
+                asteroid_spawn_timer: timer,
+                wave: 2,
+                wave_timer: timer,
+                combo: SavedCombo {
+                    hits: 0,
+                    multiplier: 1,
+                    window: timer,
+                },
+                rng: SavedRng::from(&ChaCha8Rng::seed_from_u64(1)),
+                ships: Vec::new(),
+                asteroids: Vec::new(),
+                lasers: Vec::new(),
+                pickups: Vec::new(),
+            },
+        }
+    }
+
+    #[test]
+    fn load_accepts_only_the_current_version() {
+        let path = std::env::temp_dir().join(format!("supernova-save-test-{}.json", std::process::id()));
+        empty_save(SAVE_VERSION).write(&path).unwrap();
+        let loaded = SaveGame::load(&path).unwrap();
+        assert_eq!(loaded, empty_save(SAVE_VERSION));
+
+        empty_save(SAVE_VERSION + 1).write(&path).unwrap();
+        let error = SaveGame::load(&path).unwrap_err();
+        assert!(error.contains("version"), "{error}");
+        fs::remove_file(&path).unwrap();
+    }
+
+    fn capture(app: &mut App) -> Option<MatchState> {
+        app.world_mut()
+            .run_system_once(|reader: MatchStateReader| reader.capture())
+            .unwrap()
+    }
+
+    // 在完整的应用里存档、读档再记录一次，与存档的内容相同
+    #[test]
+    fn restored_game_matches_the_save() {
+        let path = std::env::temp_dir().join(format!("supernova-restore-test-{}.json", std::process::id()));
+        let mut app = crate::test_app();
+        app.world_mut().resource_mut::<SaveConfig>().path = path.clone();
+        *app.world_mut().resource_mut::<GameMode>() = GameMode::Widescreen;
+        app.world_mut().resource_mut::<NextState<AppState>>().set(AppState::Game);
+        // 第一颗小行星 5 秒后生成
+        for _ in 0..400 {
+            app.update();
+        }
+        app.world_mut().resource_mut::<NextState<GameState>>().set(GameState::Paused);
+        app.update();
+        app.world_mut().send_event(SaveAndQuitEvent);
+        app.update();
+        app.update();
+        assert_eq!(*app.world().resource::<State<AppState>>().get(), AppState::Menu);
+
+        let save = SaveGame::load(&path).unwrap();
+        assert_eq!(save.mode, SavedMode::Widescreen);
+        assert!(!save.state.asteroids.is_empty());
+        // 与主菜单的 Continue 相同
+        *app.world_mut().resource_mut::<GameMode>() = save.mode.into();
+        app.insert_resource(RestoreGame(save.clone()));
+        app.world_mut().resource_mut::<NextState<AppState>>().set(AppState::Game);
+        for _ in 0..10 {
+            if !app.world().contains_resource::<RestoreGame>() {
+                break;
+            }
+            app.update();
+        }
+        assert!(!app.world().contains_resource::<RestoreGame>());
+        // 恢复的这一帧之后还没有推进固定时间步
+        let restored = SaveGame::new(*app.world().resource::<GameMode>(), capture(&mut app).unwrap());
+        assert_eq!(restored, save);
+        assert!(!path.exists());
+    }
+}
@@ -0,0 +1,797 @@
+use std::io::{ErrorKind, Read, Write};
+use std::net::{Ipv4Addr, SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use crate::prelude::*;
+
+// 观战快照流的协议版本，格式有不兼容的改动时加一
+pub const SPECTATOR_PROTOCOL_VERSION: u32 = 2;
+// 默认端口（TCP，与联机的 UDP 端口使用同一个数字）
+pub const SPECTATOR_DEFAULT_PORT: u16 = NET_DEFAULT_PORT;
+// 每秒发送的快照数
+const SPECTATOR_SNAPSHOT_RATE: f32 = 30.0;
+// 连接观战主机的超时时间
+const SPECTATOR_CONNECT_TIMEOUT: Duration = Duration::from_secs(2);
+// 观众来不及接收时最多积压的数据量（字节），超过后断开该观众
+const SPECTATOR_MAX_PENDING: usize = 256 * 1024;
+// 单行消息的最大长度（字节），防止异常数据占满内存
+const SPECTATOR_MAX_LINE: usize = 1024 * 1024;
+
+// 观战（--broadcast / --spectate 或联机界面的 Watch）
+//
+// 广播方在 TCP 端口上监听，观众连接后先收到一条 hello，之后每秒收到 SPECTATOR_SNAPSHOT_RATE 条快照。
+// 每条消息是一行 JSON（以换行结尾），用 type 字段区分。每条快照都包含完整的画面状态，
+// 观众随时加入都能立刻看到当前画面，也不需要补发丢失的数据。
+//
+// 版本 2 的消息：
+//
+//   {"type":"hello","version":2,"rate":30}
+//       连接后的第一条消息。version 与本机的 SPECTATOR_PROTOCOL_VERSION 不一致时观众断开连接
+//   {"type":"snapshot","tick":1234,"state":"running","mode":"classic","score":1200,"wave":2,"multiplier":1,
+//    "ships":[[1,12.5,-40.0,1.571,3,false]],
+//    "asteroids":[[300.0,120.5,0.25,0,1]],
+//    "lasers":[[20.0,15.5,1.571]]}
+//       tick      快照序号，从 0 开始递增
+//       state     menu（不在游戏中）、running、paused 或 over
+//       mode      classic、widescreen 或 big_arena，决定竞技场大小
+//       wave      当前波次，从 1 开始；不在游戏中时为 0
+//       ships     [玩家ID, x, y, 朝向（弧度）, 生命, 是否无敌]，被击毁后等待复活的飞船不在其中
+//       asteroids [x, y, 朝向, 大小, 材质]，大小为 AsteroidSize::ALL 的下标（0 大、1 中、2 小），
+//                 材质为 AsteroidMaterial::ALL 的下标（0 岩石、1 铁、2 冰、3 爆炸、4 水晶）
+//       lasers    [x, y, 朝向]
+//
+// 坐标为竞技场坐标（原点在中心，y 轴向上），保留一位小数；新版本可能增加字段，观众应忽略不认识的字段。
+// 版本 1 的 ships 包含等待复活的飞船（停在被击毁的位置），观众会看到一艘不动的飞船。
+//
+// 本机测试：
+//   cargo run -- --autopilot --broadcast
+//   cargo run -- --spectate 127.0.0.1:7777
+
+// 观战设置（资源），通过命令行参数开启
+#[derive(Resource, Debug, Default, Clone)]
+pub struct SpectatorConfig {
+    /// 在该端口广播本机的游戏画面（--broadcast [端口]）
+    pub broadcast_port: Option<u16>,
+    /// 启动后直接观看该地址的广播（--spectate 地址:端口）
+    pub spectate_addr: Option<String>,
+}
+
+// 观战流中的一条消息
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum SpectatorMessage {
+    Hello { version: u32, rate: f32 },
+    Snapshot(Snapshot),
+}
+
+// 一帧的完整画面状态
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Snapshot {
+    pub tick: u64,
+    pub state: SnapshotState,
+    pub mode: SnapshotMode,
+    pub score: u32,
+    pub wave: usize,
+    pub multiplier: u32,
+    /// [玩家ID, x, y, 朝向, 生命, 是否无敌]
+    pub ships: Vec<(u32, f32, f32, f32, u32, bool)>,
+    /// [x, y, 朝向, 大小下标, 材质下标]
+    pub asteroids: Vec<(f32, f32, f32, u8, u8)>,
+    /// [x, y, 朝向]
+    pub lasers: Vec<(f32, f32, f32)>,
+}
+
+// 快照中的游戏状态
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SnapshotState {
+    #[default]
+    Menu,
+    Running,
+    Paused,
+    Over,
+}
+
+// 快照中的游戏模式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SnapshotMode {
+    #[default]
+    Classic,
+    Widescreen,
+    BigArena,
+}
+
+impl From<GameMode> for SnapshotMode {
+    fn from(mode: GameMode) -> Self {
+        match mode {
+            GameMode::Classic => SnapshotMode::Classic,
+            GameMode::Widescreen => SnapshotMode::Widescreen,
+            GameMode::BigArena => SnapshotMode::BigArena,
+        }
+    }
+}
+
+impl From<SnapshotMode> for GameMode {
+    fn from(mode: SnapshotMode) -> Self {
+        match mode {
+            SnapshotMode::Classic => GameMode::Classic,
+            SnapshotMode::Widescreen => GameMode::Widescreen,
+            SnapshotMode::BigArena => GameMode::BigArena,
+        }
+    }
+}
+
+// 坐标和角度保留一位（角度三位）小数，减小数据量
+fn round1(value: f32) -> f32 {
+    (value * 10.0).round() / 10.0
+}
+
+fn round3(value: f32) -> f32 {
+    (value * 1000.0).round() / 1000.0
+}
+
+// 实体的位置和朝向（弧度）
+fn pose(transform: &Transform) -> (f32, f32, f32) {
+    let angle = transform.rotation.to_euler(EulerRot::ZYX).0;
+    (
+        round1(transform.translation.x),
+        round1(transform.translation.y),
+        round3(angle),
+    )
+}
+
+// 一个正在观看的观众
+struct SpectatorPeer {
+    stream: TcpStream,
+    addr: SocketAddr,
+    // 还没有发出去的数据
+    pending: Vec<u8>,
+}
+
+impl SpectatorPeer {
+    // 尽量发出积压的数据，连接出错或积压过多时返回 false
+    fn flush(&mut self) -> bool {
+        while !self.pending.is_empty() {
+            match self.stream.write(&self.pending) {
+                Ok(0) => return false,
+                Ok(written) => {
+                    self.pending.drain(..written);
+                }
+                Err(error) if error.kind() == ErrorKind::WouldBlock => break,
+                Err(_) => return false,
+            }
+        }
+        self.pending.len() <= SPECTATOR_MAX_PENDING
+    }
+}
+
+// 观战广播（资源），--broadcast 时创建
+#[derive(Resource)]
+pub struct SpectatorServer {
+    listener: TcpListener,
+    peers: Vec<SpectatorPeer>,
+    timer: Timer,
+    tick: u64,
+}
+
+impl SpectatorServer {
+    pub fn bind(port: u16) -> std::io::Result<Self> {
+        let listener = TcpListener::bind((Ipv4Addr::UNSPECIFIED, port))?;
+        listener.set_nonblocking(true)?;
+        Ok(Self {
+            listener,
+            peers: Vec::new(),
+            timer: Timer::from_seconds(1.0 / SPECTATOR_SNAPSHOT_RATE, TimerMode::Repeating),
+            tick: 0,
+        })
+    }
+}
+
+// 把消息编码成一行 JSON
+fn encode_line(message: &SpectatorMessage) -> Vec<u8> {
+    let mut line = serde_json::to_vec(message).unwrap_or_default();
+    line.push(b'\n');
+    line
+}
+
+// 观众的连接状态
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SpectatorStatus {
+    Connected,
+    Lost(String),
+}
+
+// 正在观看的广播（资源），进入观战时创建，离开时移除
+#[derive(Resource)]
+pub struct SpectatorClient {
+    stream: TcpStream,
+    pub addr: SocketAddr,
+    pub status: SpectatorStatus,
+    // 还没有读完一行的数据
+    buffer: Vec<u8>,
+    // 是否已经收到并确认了 hello
+    greeted: bool,
+    /// 最近收到的快照
+    pub snapshot: Option<Snapshot>,
+    // 最近的快照是否还没有显示
+    fresh: bool,
+    /// 收到的快照数
+    pub received: u64,
+}
+
+impl SpectatorClient {
+    // 连接观战广播，地址没有写端口时使用默认端口
+    pub fn connect(address: &str) -> Result<Self, String> {
+        let address = if address.contains(':') {
+            address.to_string()
+        } else {
+            format!("{address}:{SPECTATOR_DEFAULT_PORT}")
+        };
+        let addr = address
+            .to_socket_addrs()
+            .ok()
+            .and_then(|mut addrs| addrs.next())
+            .ok_or_else(|| format!("invalid address `{address}`"))?;
+        let stream = TcpStream::connect_timeout(&addr, SPECTATOR_CONNECT_TIMEOUT)
+            .and_then(|stream| stream.set_nonblocking(true).map(|_| stream))
+            .map_err(|error| format!("cannot watch {addr}: {error}"))?;
+        info!("Spectating {addr}");
+        Ok(Self {
+            stream,
+            addr,
+            status: SpectatorStatus::Connected,
+            buffer: Vec::new(),
+            greeted: false,
+            snapshot: None,
+            fresh: false,
+            received: 0,
+        })
+    }
+
+    // 读取所有已到达的数据，只保留最新的快照
+    fn receive(&mut self) {
+        if self.status != SpectatorStatus::Connected {
+            return;
+        }
+        let mut chunk = [0u8; 16 * 1024];
+        loop {
+            match self.stream.read(&mut chunk) {
+                Ok(0) => {
+                    self.status = SpectatorStatus::Lost("broadcast ended".to_string());
+                    break;
+                }
+                Ok(len) => self.buffer.extend_from_slice(&chunk[..len]),
+                Err(error) if error.kind() == ErrorKind::WouldBlock => break,
+                Err(error) => {
+                    self.status = SpectatorStatus::Lost(error.to_string());
+                    break;
+                }
+            }
+        }
+        while let Some(end) = self.buffer.iter().position(|byte| *byte == b'\n') {
+            let line: Vec<u8> = self.buffer.drain(..=end).collect();
+            match serde_json::from_slice::<SpectatorMessage>(&line) {
+                Ok(SpectatorMessage::Hello { version, .. }) => {
+                    if version != SPECTATOR_PROTOCOL_VERSION {
+                        self.status = SpectatorStatus::Lost(format!(
+                            "broadcast uses protocol version {version}, we use {SPECTATOR_PROTOCOL_VERSION}"
+                        ));
+                        return;
+                    }
+                    self.greeted = true;
+                }
+                Ok(SpectatorMessage::Snapshot(snapshot)) if self.greeted => {
+                    self.snapshot = Some(snapshot);
+                    self.fresh = true;
+                    self.received += 1;
+                }
+                Ok(SpectatorMessage::Snapshot(_)) => {}
+                Err(error) => warn!("Ignoring malformed spectator message: {error}"),
+            }
+        }
+        if self.buffer.len() > SPECTATOR_MAX_LINE {
+            self.status = SpectatorStatus::Lost("malformed stream".to_string());
+        }
+    }
+}
+
+// 观战画面中的飞船，数值为玩家ID
+#[derive(Component)]
+pub struct SpectatedShip(pub u32);
+
+// 观战画面中的小行星
+#[derive(Component)]
+struct SpectatedAsteroid;
+
+// 观战画面中的激光
+#[derive(Component)]
+struct SpectatedLaser;
+
+// 观战画面的状态文字
+#[derive(Component)]
+struct SpectatorHudText;
+
+// 观战插件：广播本机画面，或者只读地显示别人的广播
+pub struct SpectatorPlugin;
+
+impl Plugin for SpectatorPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SpectatorConfig>()
+            .add_systems(Startup, start_broadcast)
+            .add_systems(Last, spectator_broadcast_system.run_if(resource_exists::<SpectatorServer>))
+            .add_systems(Update, spectator_auto_start.run_if(in_state(AppState::Menu)))
+            .add_systems(OnEnter(AppState::Spectate), spawn_spectator_hud)
+            .add_systems(
+                Update,
+                (
+                    spectator_receive_system,
+                    spectator_sync_system,
+                    spectator_hud_system,
+                    spectator_exit_system,
+                )
+                    .chain()
+                    .run_if(in_state(AppState::Spectate).and(resource_exists::<SpectatorClient>)),
+            )
+            .add_systems(OnExit(AppState::Spectate), stop_spectating);
+    }
+}
+
+// --broadcast 时启动广播
+fn start_broadcast(mut commands: Commands, config: Res<SpectatorConfig>) {
+    let Some(port) = config.broadcast_port else {
+        return;
+    };
+    match SpectatorServer::bind(port) {
+        Ok(server) => {
+            info!("Broadcasting spectator stream on port {port}");
+            commands.insert_resource(server);
+        }
+        Err(error) => error!("Cannot broadcast on port {port}: {error}"),
+    }
+}
+
+#[allow(clippy::type_complexity)]
+// 接受新的观众，并按固定频率向所有观众发送快照
+fn spectator_broadcast_system(
+    time: Res<Time<Real>>,
+    mut server: ResMut<SpectatorServer>,
+    game_mode: Res<GameMode>,
+    game_state: Option<Res<State<GameState>>>,
+    arena: Option<Res<Arena>>,
+    wave: Option<Res<Wave>>,
+    combo: Res<Combo>,
+    ships: Query<(&Ship, &Transform), Without<Respawning>>,
+    asteroids: Query<(&Asteroid, &Transform)>,
+    lasers: Query<&Transform, With<Laser>>,
+) {
+    let server = server.as_mut();
+    // 新观众先收到 hello，随后和其他观众一起收到下一条完整快照
+    while let Ok((stream, addr)) = server.listener.accept() {
+        if stream.set_nonblocking(true).is_err() {
+            continue;
+        }
+        info!("Spectator connected from {addr}");
+        server.peers.push(SpectatorPeer {
+            stream,
+            addr,
+            pending: encode_line(&SpectatorMessage::Hello {
+                version: SPECTATOR_PROTOCOL_VERSION,
+                rate: SPECTATOR_SNAPSHOT_RATE,
+            }),
+        });
+    }
+    if !server.timer.tick(time.delta()).just_finished() || server.peers.is_empty() {
+        return;
+    }
+
+    let state = match game_state.map(|state| *state.get()) {
+        None => SnapshotState::Menu,
+        Some(GameState::Setup | GameState::Running) => SnapshotState::Running,
+        Some(GameState::Paused) => SnapshotState::Paused,
+        Some(GameState::Over) => SnapshotState::Over,
+    };
+    let in_game = state != SnapshotState::Menu;
+    let mut snapshot = Snapshot {
+        tick: server.tick,
+        state,
+        mode: (*game_mode).into(),
+        score: arena.map_or(0, |arena| arena.score),
+        wave: wave.map_or(0, |wave| wave.index + 1),
+        multiplier: combo.multiplier,
+        ..default()
+    };
+    if in_game {
+        snapshot.ships = ships
+            .iter()
+            .map(|(ship, transform)| {
+                let (x, y, angle) = pose(transform);
+                (ship.player_id, x, y, angle, ship.life, !ship.invincible_timer.finished())
+            })
+            .collect();
+        snapshot.ships.sort_by_key(|ship| ship.0);
+        snapshot.asteroids = asteroids
+            .iter()
+            .map(|(asteroid, transform)| {
+                let (x, y, angle) = pose(transform);
+                let size = AsteroidSize::ALL.iter().position(|size| *size == asteroid.size);
+                let material = AsteroidMaterial::ALL
+                    .iter()
+                    .position(|material| *material == asteroid.material);
+                (x, y, angle, size.unwrap_or(0) as u8, material.unwrap_or(0) as u8)
+            })
+            .collect();
+        snapshot.lasers = lasers.iter().map(pose).collect();
+    }
+    server.tick += 1;
+
+    let line = encode_line(&SpectatorMessage::Snapshot(snapshot));
+    server.peers.retain_mut(|peer| {
+        peer.pending.extend_from_slice(&line);
+        let alive = peer.flush();
+        if !alive {
+            info!("Spectator {} disconnected", peer.addr);
+        }
+        alive
+    });
+}
+
+// --spectate 时从主菜单直接开始观战，连接失败时退出程序
+fn spectator_auto_start(
+    mut commands: Commands,
+    config: Res<SpectatorConfig>,
+    mut started: Local<bool>,
+    mut next_app_state: ResMut<NextState<AppState>>,
+    mut app_exit_events: EventWriter<AppExit>,
+) {
+    let Some(address) = &config.spectate_addr else {
+        return;
+    };
+    if *started {
+        return;
+    }
+    *started = true;
+    match SpectatorClient::connect(address) {
+        Ok(client) => {
+            commands.insert_resource(client);
+            next_app_state.set(AppState::Spectate);
+        }
+        Err(error) => {
+            error!("{error}");
+            app_exit_events.write(AppExit::error());
+        }
+    }
+}
+
+// 进入观战时生成状态文字
+fn spawn_spectator_hud(mut commands: Commands, assets: Res<UiAssets>) {
+    commands.spawn((
+        Node {
+            position_type: PositionType::Absolute,
+            width: Val::Percent(100.0),
+            justify_content: JustifyContent::Center,
+            top: Val::Px(10.0),
+            ..default()
+        },
+        StateScoped(AppState::Spectate),
+        children![(
+            SpectatorHudText,
+            Text::default(),
+            TextFont {
+                font: assets.font_fira.clone(),
+                font_size: 22.0,
+                ..default()
+            },
+            TextColor(Color::srgb(0.0, 0.7, 0.7)),
+            TextLayout::new_with_justify(JustifyText::Center),
+        )],
+    ));
+}
+
+fn spectator_receive_system(mut client: ResMut<SpectatorClient>) {
+    client.receive();
+}
+
+#[allow(clippy::type_complexity)]
+// 按最新的快照更新画面中的飞船、小行星和激光，数量变化时增减实体
+fn spectator_sync_system(
+    mut commands: Commands,
+    mut client: ResMut<SpectatorClient>,
+    handles: Res<SpriteAssets>,
+    mut game_mode: ResMut<GameMode>,
+    mut ships: Query<(Entity, &SpectatedShip, &mut Transform, &mut Sprite)>,
+    mut asteroids: Query<
+        (Entity, &mut Transform, &mut Sprite),
+        (With<SpectatedAsteroid>, Without<SpectatedShip>),
+    >,
+    mut lasers: Query<
+        (Entity, &mut Transform),
+        (With<SpectatedLaser>, Without<SpectatedAsteroid>, Without<SpectatedShip>),
+    >,
+) {
+    if !client.fresh {
+        return;
+    }
+    client.fresh = false;
+    let Some(snapshot) = &client.snapshot else {
+        return;
+    };
+    let mode = GameMode::from(snapshot.mode);
+    if *game_mode != mode {
+        *game_mode = mode;
+    }
+    let transform_of = |x: f32, y: f32, angle: f32, z: f32| {
+        Transform::from_xyz(x, y, z).with_rotation(Quat::from_rotation_z(angle))
+    };
+
+    // 飞船按玩家ID对应
+    for (entity, ship, mut transform, mut sprite) in ships.iter_mut() {
+        match snapshot.ships.iter().find(|state| state.0 == ship.0) {
+            Some(&(_, x, y, angle, _, invincible)) => {
+                *transform = transform_of(x, y, angle, 1.0);
+                sprite.color = if invincible {
+                    Color::srgba(1.0, 0.4, 0.2, 0.6)
+                } else {
+                    Color::WHITE
+                };
+            }
+            None => commands.entity(entity).despawn(),
+        }
+    }
+    for &(player_id, x, y, angle, _, _) in snapshot.ships.iter() {
+        if !ships.iter().any(|(_, ship, _, _)| ship.0 == player_id) {
+            commands.spawn((
+                Name::new(format!("SpectatedShip {player_id}")),
+                SpectatedShip(player_id),
+                Sprite {
+                    image: handles.player_ship.clone(),
+                    custom_size: Some(Vec2::new(30., 20.)),
+                    ..default()
+                },
+                transform_of(x, y, angle, 1.0),
+                StateScoped(AppState::Spectate),
+            ));
+        }
+    }
+
+    // 小行星和激光没有身份，按顺序复用已有的实体
+    let mut existing = asteroids.iter_mut();
+    for &(x, y, angle, size, material) in snapshot.asteroids.iter() {
+        let size = AsteroidSize::ALL[(size as usize).min(AsteroidSize::ALL.len() - 1)];
+        let material =
+            AsteroidMaterial::ALL[(material as usize).min(AsteroidMaterial::ALL.len() - 1)];
+        let image = handles.meteor(material, size);
+        match existing.next() {
+            Some((_, mut transform, mut sprite)) => {
+                *transform = transform_of(x, y, angle, 1.0);
+                if sprite.image != image {
+                    sprite.image = image;
+                }
+            }
+            None => {
+                commands.spawn((
+                    Name::new("SpectatedAsteroid"),
+                    SpectatedAsteroid,
+                    Sprite { image, ..default() },
+                    transform_of(x, y, angle, 1.0),
+                    StateScoped(AppState::Spectate),
+                ));
+            }
+        }
+    }
+    for (entity, _, _) in existing {
+        commands.entity(entity).despawn();
+    }
+
+    let mut existing = lasers.iter_mut();
+    for &(x, y, angle) in snapshot.lasers.iter() {
+        match existing.next() {
+            Some((_, mut transform)) => *transform = transform_of(x, y, angle, 0.5),
+            None => {
+                commands.spawn((
+                    Name::new("SpectatedLaser"),
+                    SpectatedLaser,
+                    Sprite {
+                        image: handles.laser.clone(),
+                        custom_size: Some(Vec2::new(5., 20.0)),
+                        ..default()
+                    },
+                    transform_of(x, y, angle, 0.5),
+                    StateScoped(AppState::Spectate),
+                ));
+            }
+        }
+    }
+    for (entity, _) in existing {
+        commands.entity(entity).despawn();
+    }
+}
+
+// 更新观战状态文字：分数、波次、生命和对局状态
+fn spectator_hud_system(
+    client: Res<SpectatorClient>,
+    mut texts: Query<&mut Text, With<SpectatorHudText>>,
+) {
+    let mut text = format!("Spectating {}", client.addr);
+    match (&client.status, &client.snapshot) {
+        (SpectatorStatus::Lost(reason), _) => {
+            text.push_str(&format!("\nDisconnected: {reason}\nPress Esc to return"));
+        }
+        (SpectatorStatus::Connected, None) => text.push_str("\nWaiting for the broadcast..."),
+        (SpectatorStatus::Connected, Some(snapshot)) => match snapshot.state {
+            SnapshotState::Menu => text.push_str("\nWaiting for the next game..."),
+            state => {
+                text.push_str(&format!(
+                    "\nScore {}  Wave {}  x{}",
+                    snapshot.score, snapshot.wave, snapshot.multiplier
+                ));
+                for &(player_id, _, _, _, life, _) in snapshot.ships.iter() {
+                    text.push_str(&format!("  P{player_id} {life}"));
+                }
+                if state == SnapshotState::Paused {
+                    text.push_str("\nPAUSED");
+                } else if state == SnapshotState::Over {
+                    text.push_str("\nGAME OVER");
+                }
+            }
+        },
+    }
+    for mut hud_text in texts.iter_mut() {
+        if hud_text.0 != text {
+            hud_text.0.clone_from(&text);
+        }
+    }
+}
+
+// 按 Esc 离开观战；命令行直接开始的观战在连接断开后退出程序
+fn spectator_exit_system(
+    client: Res<SpectatorClient>,
+    config: Res<SpectatorConfig>,
+    menu_action_state: Res<ActionState<MenuAction>>,
+    mut next_app_state: ResMut<NextState<AppState>>,
+    mut app_exit_events: EventWriter<AppExit>,
+) {
+    if menu_action_state.just_pressed(&MenuAction::PauseUnpause) {
+        next_app_state.set(AppState::Menu);
+    }
+    if let SpectatorStatus::Lost(reason) = &client.status {
+        if config.spectate_addr.is_some() {
+            info!(
+                "Spectator stream from {} lost: {reason}, {} snapshots received",
+                client.addr, client.received
+            );
+            app_exit_events.write(AppExit::Success);
+        }
+    }
+}
+
+// 离开观战：断开连接，打印收到的快照数
+fn stop_spectating(mut commands: Commands, client: Option<Res<SpectatorClient>>) {
+    if let Some(client) = client {
+        info!(
+            "Stopped spectating {}: {} snapshots received",
+            client.addr, client.received
+        );
+        commands.remove_resource::<SpectatorClient>();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Instant;
+
+    use super::*;
+
+    // 建立一个连接到本机的观众，返回观众和广播方一侧的连接
+    fn spectator_pair() -> (SpectatorClient, TcpStream) {
+        let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0)).unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let client = SpectatorClient::connect(&format!("127.0.0.1:{port}")).unwrap();
+        let (stream, _) = listener.accept().unwrap();
+        (client, stream)
+    }
+
+    // 反复接收，直到 done 返回 true
+    fn receive_until(client: &mut SpectatorClient, mut done: impl FnMut(&SpectatorClient) -> bool) {
+        let deadline = Instant::now() + Duration::from_secs(10);
+        while !done(client) {
+            assert!(Instant::now() < deadline, "spectator did not receive in time");
+            std::thread::sleep(Duration::from_millis(1));
+            client.receive();
+        }
+    }
+
+    fn hello(version: u32) -> Vec<u8> {
+        encode_line(&SpectatorMessage::Hello {
+            version,
+            rate: SPECTATOR_SNAPSHOT_RATE,
+        })
+    }
+
+    fn test_snapshot(tick: u64) -> Snapshot {
+        Snapshot {
+            tick,
+            state: SnapshotState::Running,
+            mode: SnapshotMode::BigArena,
+            score: 1200,
+            wave: 2,
+            multiplier: 3,
+            ships: vec![(1, 12.5, -40.0, 1.571, 3, false), (2, -8.0, 0.5, -0.25, 1, true)],
+            asteroids: vec![(300.0, 120.5, 0.25, 0, 1)],
+            lasers: vec![(20.0, 15.5, 1.571)],
+        }
+    }
+
+    #[test]
+    fn snapshots_round_trip_through_the_stream() {
+        let (mut client, mut stream) = spectator_pair();
+        let snapshot = SpectatorMessage::Snapshot(test_snapshot(7));
+        let line = encode_line(&snapshot);
+        assert_eq!(line.last(), Some(&b'\n'));
+        assert_eq!(serde_json::from_slice::<SpectatorMessage>(&line).unwrap(), snapshot);
+
+        // hello 之前的快照被忽略
+        stream.write_all(&encode_line(&SpectatorMessage::Snapshot(test_snapshot(6)))).unwrap();
+        stream.write_all(&hello(SPECTATOR_PROTOCOL_VERSION)).unwrap();
+        stream.write_all(&line).unwrap();
+        receive_until(&mut client, |client| client.received > 0);
+        assert_eq!(client.status, SpectatorStatus::Connected);
+        assert_eq!(client.received, 1);
+        assert_eq!(client.snapshot, Some(test_snapshot(7)));
+    }
+
+    #[test]
+    fn lines_split_across_reads_are_joined() {
+        let (mut client, mut stream) = spectator_pair();
+        let mut data = hello(SPECTATOR_PROTOCOL_VERSION);
+        data.extend(encode_line(&SpectatorMessage::Snapshot(test_snapshot(1))));
+        data.extend(encode_line(&SpectatorMessage::Snapshot(test_snapshot(2))));
+        // 每次发送的数据都在一行的中间断开
+        for chunk in data.chunks(37) {
+            stream.write_all(chunk).unwrap();
+            stream.flush().unwrap();
+            std::thread::sleep(Duration::from_millis(1));
+            client.receive();
+        }
+        receive_until(&mut client, |client| client.received == 2);
+        assert_eq!(client.status, SpectatorStatus::Connected);
+        assert_eq!(client.snapshot, Some(test_snapshot(2)));
+    }
+
+    #[test]
+    fn hello_with_another_version_is_rejected() {
+        let (mut client, mut stream) = spectator_pair();
+        stream.write_all(&hello(SPECTATOR_PROTOCOL_VERSION + 1)).unwrap();
+        stream.write_all(&encode_line(&SpectatorMessage::Snapshot(test_snapshot(1)))).unwrap();
+        receive_until(&mut client, |client| client.status != SpectatorStatus::Connected);
+        let SpectatorStatus::Lost(reason) = &client.status else {
+            unreachable!();
+        };
+        assert!(reason.contains("version"), "{reason}");
+        assert_eq!(client.snapshot, None);
+    }
+
+    #[test]
+    fn overlong_line_drops_the_connection() {
+        let (mut client, mut stream) = spectator_pair();
+        stream.write_all(&hello(SPECTATOR_PROTOCOL_VERSION)).unwrap();
+        // 没有换行的数据超过 SPECTATOR_MAX_LINE 后断开，而不是一直缓存下去
+        stream.set_nonblocking(true).unwrap();
+        let junk = vec![b'x'; 64 * 1024];
+        let mut written = 0;
+        receive_until(&mut client, |client| {
+            if client.status != SpectatorStatus::Connected {
+                return true;
+            }
+            if let Ok(len) = stream.write(&junk) {
+                written += len;
+            }
+            false
+        });
+        assert_eq!(client.status, SpectatorStatus::Lost("malformed stream".to_string()));
+        assert!(written > SPECTATOR_MAX_LINE);
+        assert!(client.greeted);
+    }
+}
@@ -0,0 +1,95 @@
+use bevy::platform::collections::HashMap;
+
+use crate::prelude::*;
+
+// 透明度高于该值的像素视为精灵实体部分
+const ALPHA_THRESHOLD: f32 = 0.5;
+
+// 按精灵图片缓存的碰撞体，由图片的不透明轮廓生成凸包
+// 图片加载完成后生成一次，之后所有使用该图片的实体共用
+#[derive(Resource, Default)]
+pub struct SpriteColliders {
+    colliders: HashMap<AssetId<Image>, Collider>,
+}
+
+impl SpriteColliders {
+    // 获取图片对应的碰撞体，图片尚未加载完成时返回 None
+    pub fn get(&self, image: &Handle<Image>) -> Option<Collider> {
+        self.colliders.get(&image.id()).cloned()
+    }
+
+    // 是否已经为所有小行星精灵生成了碰撞体
+    pub fn is_complete(&self, sprites: &SpriteAssets) -> bool {
+        self.colliders.len() == sprites.meteors.len()
+    }
+}
+
+// 精灵碰撞体插件，为小行星精灵生成凸包碰撞体
+pub struct SpriteColliderPlugin;
+
+impl Plugin for SpriteColliderPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SpriteColliders>().add_systems(
+            Update,
+            build_sprite_colliders.run_if(resource_exists::<SpriteAssets>),
+        );
+    }
+}
+
+// 为已加载但还没有碰撞体的小行星精灵生成凸包
+fn build_sprite_colliders(
+    sprites: Res<SpriteAssets>,
+    images: Res<Assets<Image>>,
+    mut colliders: ResMut<SpriteColliders>,
+) {
+    if colliders.is_complete(&sprites) {
+        return; // 全部生成完毕
+    }
+    for handle in sprites.meteors.values() {
+        if colliders.colliders.contains_key(&handle.id()) {
+            continue;
+        }
+        let Some(image) = images.get(handle) else {
+            continue;
+        };
+        if let Some(collider) = convex_hull_from_alpha(image) {
+            colliders.colliders.insert(handle.id(), collider);
+        }
+    }
+}
+
+// 取每一行最左和最右的不透明像素的四个角，计算它们的凸包
+// 坐标以图片中心为原点、y 轴向上，与精灵原始大小一致
+fn convex_hull_from_alpha(image: &Image) -> Option<Collider> {
+    let width = image.width();
+    let height = image.height();
+    let center = Vec2::new(width as f32, height as f32) / 2.0;
+    let opaque = |x: u32, y: u32| {
+        image
+            .get_color_at(x, y)
+            .is_ok_and(|color| color.alpha() > ALPHA_THRESHOLD)
+    };
+
+    let mut points = Vec::new();
+    for y in 0..height {
+        let Some(left) = (0..width).find(|x| opaque(*x, y)) else {
+            continue;
+        };
+        let right = (0..width).rev().find(|x| opaque(*x, y)).unwrap_or(left);
+        for x in [left as f32, right as f32 + 1.0] {
+            for corner_y in [y as f32, y as f32 + 1.0] {
+                points.push(Vec2::new(x - center.x, center.y - corner_y));
+            }
+        }
+    }
+    Collider::convex_hull(points)
+}
+
+// 按 F3 显示或隐藏物理调试图形（碰撞体轮廓等），仅调试版本可用
+#[cfg(debug_assertions)]
+pub fn toggle_physics_debug(keys: Res<ButtonInput<KeyCode>>, mut store: ResMut<GizmoConfigStore>) {
+    if keys.just_pressed(KeyCode::F3) {
+        let (config, _) = store.config_mut::<PhysicsGizmos>();
+        config.enabled = !config.enabled;
+    }
+}
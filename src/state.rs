@@ -9,6 +9,8 @@ pub enum AppState {
     Menu,    // 菜单界面状态
     Game,    // 游戏进行中状态
     Credits, // 制作人员名单界面状态
+    Lobby,   // 联机界面状态
+    Spectate, // 观战状态，只读地显示别人广播的画面
 }
 
 // 游戏主状态下的子状态枚举，进一步细化游戏内部流程
@@ -0,0 +1,202 @@
+use crate::prelude::*;
+
+// 一波中放置的引力井，位置以竞技场大小的比例表示（-0.5 ~ 0.5，中心为 0）
+#[derive(Debug, Clone, Copy)]
+pub struct GravityWellSpec {
+    pub position: Vec2,
+    pub well: GravityWell,
+}
+
+// 波次定义：持续时间、背景主题和场地布局
+#[derive(Debug, Clone)]
+pub struct WaveDefinition {
+    pub name: &'static str,
+    // 持续多久后进入下一波（秒）
+    pub duration_secs: f32,
+    // 这一波使用的背景主题
+    pub theme: BackgroundTheme,
+    // 这一波放置的引力井
+    pub gravity_wells: &'static [GravityWellSpec],
+}
+
+// 黑洞：范围大、引力强
+const BLACK_HOLE: GravityWell = GravityWell {
+    strength: 3_000_000.0,
+    radius: 450.0,
+    core_radius: 25.0,
+};
+
+// 引力井：范围小、引力弱
+const SMALL_WELL: GravityWell = GravityWell {
+    strength: 1_200_000.0,
+    radius: 280.0,
+    core_radius: 15.0,
+};
+
+// 所有波次，按顺序进行，最后一波结束后回到第二波循环
+// 竞技场中心是飞船重生点，引力井都放在离中心较远的位置
+pub const WAVES: &[WaveDefinition] = &[
+    WaveDefinition {
+        name: "Open Space",
+        duration_secs: 45.0,
+        theme: BackgroundTheme::DEEP_SPACE,
+        gravity_wells: &[],
+    },
+    WaveDefinition {
+        name: "Singularity",
+        duration_secs: 60.0,
+        theme: BackgroundTheme::CLASSIC,
+        gravity_wells: &[GravityWellSpec {
+            position: Vec2::new(0.28, 0.22),
+            well: BLACK_HOLE,
+        }],
+    },
+    WaveDefinition {
+        name: "Twin Wells",
+        duration_secs: 60.0,
+        theme: BackgroundTheme::AURORA,
+        gravity_wells: &[
+            GravityWellSpec {
+                position: Vec2::new(-0.3, -0.25),
+                well: SMALL_WELL,
+            },
+            GravityWellSpec {
+                position: Vec2::new(0.3, 0.25),
+                well: SMALL_WELL,
+            },
+        ],
+    },
+    WaveDefinition {
+        name: "Maelstrom",
+        duration_secs: 60.0,
+        theme: BackgroundTheme::CRIMSON,
+        gravity_wells: &[
+            GravityWellSpec {
+                position: Vec2::new(-0.32, 0.25),
+                well: SMALL_WELL,
+            },
+            GravityWellSpec {
+                position: Vec2::new(0.32, 0.25),
+                well: SMALL_WELL,
+            },
+            GravityWellSpec {
+                position: Vec2::new(0.0, -0.3),
+                well: BLACK_HOLE,
+            },
+        ],
+    },
+];
+
+// 当前波次（资源）
+#[derive(Debug, Resource)]
+pub struct Wave {
+    // 当前波次在 WAVES 中的下标
+    pub index: usize,
+    // 当前波次剩余时间
+    pub timer: Timer,
+}
+
+impl Wave {
+    // 当前波次的定义
+    pub fn definition(&self) -> &'static WaveDefinition {
+        &WAVES[self.index % WAVES.len()]
+    }
+}
+
+// 开始指定波次的事件（下标从 0 开始）
+#[derive(Event, Debug, Clone, Copy)]
+pub struct StartWave(pub usize);
+
+// 波次插件，负责按时间推进波次，并按波次定义布置场地和背景
+pub struct WavePlugin;
+
+impl Plugin for WavePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<StartWave>()
+            .add_systems(OnEnter(GameState::Setup), |mut events: EventWriter<StartWave>| {
+                events.write(StartWave(0));
+            })
+            // 从暂停恢复时重新使用当前波次的背景主题
+            .add_systems(OnEnter(GameState::Running), apply_wave_theme)
+            .add_systems(
+                FixedUpdate,
+                (
+                    wave_timer_system.run_if(in_state(GameState::Running)),
+                    start_wave,
+                )
+                    .chain()
+                    .run_if(in_state(AppState::Game)),
+            );
+    }
+}
+
+// 当前波次时间到后进入下一波
+fn wave_timer_system(
+    time: Res<Time>,
+    wave: Option<ResMut<Wave>>,
+    mut events: EventWriter<StartWave>,
+) {
+    let Some(mut wave) = wave else {
+        return;
+    };
+    wave.timer.tick(time.delta());
+    if wave.timer.just_finished() {
+        // 最后一波结束后回到第二波，第一波作为开场不再重复
+        let next = match wave.index + 1 {
+            next if next < WAVES.len() => next,
+            _ => 1,
+        };
+        events.write(StartWave(next));
+    }
+}
+
+// 开始新的波次：替换引力井并切换背景主题
+fn start_wave(
+    mut commands: Commands,
+    mut events: EventReader<StartWave>,
+    arena: Option<Res<Arena>>,
+    handles: Res<SpriteAssets>,
+    mut theme: ResMut<CurrentBackgroundTheme>,
+    wells: Query<Entity, With<GravityWell>>,
+) {
+    let Some(StartWave(index)) = events.read().last().copied() else {
+        return;
+    };
+    let Some(arena) = arena else {
+        return;
+    };
+    let index = index % WAVES.len();
+    let definition = lay_out_wave(&mut commands, &handles, arena.size, &mut theme, &wells, index);
+    info!("Wave {}: {}", index + 1, definition.name);
+    commands.insert_resource(Wave {
+        index,
+        timer: Timer::from_seconds(definition.duration_secs, TimerMode::Once),
+    });
+}
+
+// 按波次定义布置场地：替换引力井并切换背景主题，回滚和读档时也用它恢复场地
+pub fn lay_out_wave(
+    commands: &mut Commands,
+    handles: &SpriteAssets,
+    arena_size: Vec2,
+    theme: &mut CurrentBackgroundTheme,
+    wells: &Query<Entity, With<GravityWell>>,
+    index: usize,
+) -> &'static WaveDefinition {
+    let definition = &WAVES[index % WAVES.len()];
+    for entity in wells.iter() {
+        commands.entity(entity).despawn();
+    }
+    for spec in definition.gravity_wells {
+        spawn_gravity_well(commands, handles, spec.well, spec.position * arena_size);
+    }
+    theme.0 = definition.theme;
+    definition
+}
+
+// 把背景主题设置为当前波次的主题
+fn apply_wave_theme(wave: Option<Res<Wave>>, mut theme: ResMut<CurrentBackgroundTheme>) {
+    if let Some(wave) = wave {
+        theme.0 = wave.definition().theme;
+    }
+}